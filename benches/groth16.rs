@@ -10,6 +10,7 @@ use ark_groth16::Groth16;
 use ark_std::rand::{rngs::StdRng, SeedableRng};
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use zksnark_reporting_system::hash::{AlgebraicHashGadget, Poseidon2HashVar, PoseidonHashVar};
 use zksnark_reporting_system::PoseidonCircuit;
 
 // Benchmark configs
@@ -21,9 +22,10 @@ fn criterion_config() -> Criterion {
         .output_directory(Path::new("./docs/benchmark_data"))
 }
 
-/// Criterion entry‑point
-fn groth16_bench(c: &mut Criterion) {
-    let mut group = c.benchmark_group("groth16");
+/// Benchmark the circuit family hashing with `H` under `group_name`.
+fn bench_circuit<H: AlgebraicHashGadget<Fr>>(c: &mut Criterion, group_name: &str) {
+    let make = PoseidonCircuit::<Fr, H>::random;
+    let mut group = c.benchmark_group(group_name);
     let mut rng = StdRng::seed_from_u64(42);
 
     // n = 4 ... 1024  (powers of two)
@@ -31,28 +33,25 @@ fn groth16_bench(c: &mut Criterion) {
         let n = 1u32 << exp;
 
         // Trusted setup (once per n) -------------------------------
-        let circuit = PoseidonCircuit::<Fr>::new(n);
-        let (pk, vk) = Groth16::<Bls12_377>::setup(circuit, &mut rng).unwrap();
+        let (pk, vk) = Groth16::<Bls12_377>::setup(PoseidonCircuit::<Fr, H>::new(n), &mut rng).unwrap();
         let pvk = Groth16::<Bls12_377>::process_vk(&vk).unwrap();
 
         // Proving --------------------------------------------------
         group.bench_function(BenchmarkId::new("prove", n), |b| {
             b.iter(|| {
-                let circuit = PoseidonCircuit::<Fr>::new(n);
-                Groth16::<Bls12_377>::prove(&pk, circuit, &mut rng).unwrap();
+                Groth16::<Bls12_377>::prove(&pk, make(n), &mut rng).unwrap();
             })
         });
 
         // pre‑build one proof so we can isolate verification timing
-        let proof = {
-            let circuit = PoseidonCircuit::<Fr>::new(n);
-            Groth16::<Bls12_377>::prove(&pk, circuit, &mut rng).unwrap()
-        };
+        let proof = Groth16::<Bls12_377>::prove(&pk, make(n), &mut rng).unwrap();
+        let public_inputs = PoseidonCircuit::<Fr, H>::public_inputs(make(n).inputs().unwrap());
+        assert!(Groth16::<Bls12_377>::verify_with_processed_vk(&pvk, &public_inputs, &proof).unwrap());
 
         // Verification bench ---------------------------------------
         group.bench_function(BenchmarkId::new("verify", n), |b| {
             b.iter(|| {
-                Groth16::<Bls12_377>::verify_with_processed_vk(&pvk, &[], &proof).unwrap();
+                Groth16::<Bls12_377>::verify_with_processed_vk(&pvk, &public_inputs, &proof).unwrap();
             })
         });
     }
@@ -60,6 +59,12 @@ fn groth16_bench(c: &mut Criterion) {
     group.finish();
 }
 
+/// Criterion entry‑point
+fn groth16_bench(c: &mut Criterion) {
+    bench_circuit::<PoseidonHashVar<Fr>>(c, "groth16");
+    bench_circuit::<Poseidon2HashVar<Fr>>(c, "groth16_poseidon2");
+}
+
 criterion_group!{
     name = benches;
     config = criterion_config();
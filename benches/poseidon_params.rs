@@ -0,0 +1,62 @@
+//! Criterion benchmark: Poseidon parameter generation vs. cache
+//!
+//! Compares building a sponge from freshly generated parameters
+//! with building it from the process‑wide parameter registry.
+
+use std::time::Duration;
+use ark_bn254::Fr;
+use criterion::{criterion_group, criterion_main, Criterion};
+use zksnark_reporting_system::hash::{cached_poseidon_config, get_poseidon_config, PoseidonHash};
+
+// Benchmark configs
+fn criterion_config() -> Criterion {
+    Criterion::default()
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(5))
+}
+
+/// Criterion entry‑point
+fn params_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("poseidon_params");
+
+    // Sponge construction ----------------------------------------
+    group.bench_function("new_sponge/regenerated", |b| {
+        b.iter(|| PoseidonHash::<Fr>::with_config(&get_poseidon_config::<Fr>()))
+    });
+    group.bench_function("new_sponge/cached", |b| {
+        b.iter(PoseidonHash::<Fr>::new)
+    });
+
+    // Hashing many short reports ---------------------------------
+    let reports: Vec<[Fr; 4]> = (0u64..64)
+        .map(|i| [Fr::from(i), Fr::from(i + 1), Fr::from(i + 2), Fr::from(i + 3)])
+        .collect();
+
+    group.bench_function("hash_64_reports/regenerated", |b| {
+        b.iter(|| {
+            for report in &reports {
+                let mut hash = PoseidonHash::with_config(&get_poseidon_config::<Fr>());
+                hash.absorb_many(report.iter());
+                hash.squeeze();
+            }
+        })
+    });
+    group.bench_function("hash_64_reports/cached", |b| {
+        b.iter(|| {
+            for report in &reports {
+                let mut hash = PoseidonHash::with_config(&cached_poseidon_config::<Fr>());
+                hash.absorb_many(report.iter());
+                hash.squeeze();
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!{
+    name = benches;
+    config = criterion_config();
+    targets = params_bench
+}
+criterion_main!(benches);
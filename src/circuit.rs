@@ -1,10 +1,24 @@
 
 //! Constraint system used for the system.
 //!
-//! The circuit simply hashes an n‑length vector of random field
-//! elements with Poseidon. It is intentionally minimal – its role
-//! is to stress Groth16 proving/verification so we can observe the
-//! asymptotic behavior as `n` grows.
+//! The circuit hashes an n‑length vector of field elements with
+//! Poseidon and exposes the digest as its only public input, so a proof
+//! attests to “I know a preimage of this digest”. The preimage is
+//! supplied by the caller: a circuit built with [`PoseidonCircuit::new`]
+//! carries no witness and is used for the setup, one built with
+//! [`PoseidonCircuit::with_inputs`] proves over a concrete report. The
+//! benches use [`PoseidonCircuit::random`] to stress Groth16
+//! proving/verification and observe the asymptotic behavior as `n`
+//! grows. The hash is pluggable through
+//! [`AlgebraicHashGadget`]; `Poseidon2Circuit` hashes the very same
+//! inputs with Poseidon2 for comparison.
+//!
+//! [`VarLenPoseidonCircuit`] lifts the fixed length: it is set up once
+//! for a capacity `N` and proves over any report of secret length
+//! `len ≤ N`, padded with zeros and hashed together with `len`.
+//!
+//! [`ReportCircuit`] publishes only a hiding commitment to a report and
+//! proves that each of its fields lies in a declared range.
 
 #![deny(
     trivial_casts,
@@ -16,59 +30,347 @@
     unsafe_code,
 )]
 
+use std::fmt;
 use std::marker::PhantomData;
 use ark_ff::PrimeField;
-use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::rand::{prelude::StdRng, SeedableRng};
-use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
-use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
-use crate::hash::get_poseidon_config;
+use ark_crypto_primitives::sponge::Absorb;
+use crate::hash::{AlgebraicHash, AlgebraicHashGadget, Poseidon2HashVar, PoseidonHashVar};
+
+pub mod report;
 
-/// Circuit hashing `n` random field elements.
+pub use report::ReportCircuit;
+
+/// Circuit hashing `n` field elements with the gadget `H` (Poseidon by
+/// default).
 #[derive(Clone)]
-pub struct PoseidonCircuit<F: PrimeField> {
+pub struct PoseidonCircuit<F: PrimeField + Absorb, H: AlgebraicHashGadget<F> = PoseidonHashVar<F>> {
     n: u32,
-    _field: PhantomData<F>,
+    /// The preimage; `None` during setup.
+    inputs: Option<Vec<F>>,
+    _hash: PhantomData<fn() -> (F, H)>,
 }
 
-impl<F: PrimeField> PoseidonCircuit<F> {
-    /// Create a new circuit hashing `n` elements.
-    pub fn new(n: u32) -> Self { Self { n, _field: PhantomData } }
-}
+/// [`PoseidonCircuit`] instantiated with Poseidon2.
+pub type Poseidon2Circuit<F> = PoseidonCircuit<F, Poseidon2HashVar<F>>;
 
-impl<F: PrimeField> ConstraintSynthesizer<F> for PoseidonCircuit<F> {
-    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+impl<F: PrimeField + Absorb, H: AlgebraicHashGadget<F>> PoseidonCircuit<F, H> {
+    /// Create a witness‑less circuit hashing `n` elements, for the setup.
+    pub fn new(n: u32) -> Self { Self { n, inputs: None, _hash: PhantomData } }
+
+    /// Create a circuit proving knowledge of `inputs`.
+    pub fn with_inputs(inputs: Vec<F>) -> Self {
+        Self { n: inputs.len() as u32, inputs: Some(inputs), _hash: PhantomData }
+    }
+
+    /// Benchmark constructor: hash `n` pseudo‑random elements.
+    pub fn random(n: u32) -> Self {
         // Deterministic RNG to make the circuit re‑producible across runs.
         let mut rng = StdRng::seed_from_u64(42);
+        Self::with_inputs((0..n).map(|_| F::rand(&mut rng)).collect())
+    }
 
-        let random_inputs: Vec<F> = (0..self.n).map(|_| F::rand(&mut rng)).collect();
+    /// Number of hashed elements.
+    pub fn len(&self) -> u32 { self.n }
+
+    /// Whether the circuit hashes no element.
+    pub fn is_empty(&self) -> bool { self.n == 0 }
+
+    /// The preimage, if the circuit carries one.
+    pub fn inputs(&self) -> Option<&[F]> { self.inputs.as_deref() }
+
+    /// Digest of `inputs` under the native counterpart of `H`
+    /// ([`PoseidonHash`](crate::hash::PoseidonHash) by default).
+    pub fn digest(inputs: &[F]) -> F {
+        let mut hash = H::Native::new();
+        hash.absorb_many(inputs);
+        hash.squeeze()
+    }
+
+    /// Public inputs a verifier checks a proof over `inputs` against:
+    /// their digest.
+    pub fn public_inputs(inputs: &[F]) -> Vec<F> {
+        vec![Self::digest(inputs)]
+    }
+}
+
+impl<F: PrimeField + Absorb, H: AlgebraicHashGadget<F>> ConstraintSynthesizer<F> for PoseidonCircuit<F, H> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let inputs = self.inputs.as_deref();
+
+        // Public digest -------------------------------------------------------
+        let digest = FpVar::new_input(cs.clone(), || {
+            inputs.map(Self::digest).ok_or(SynthesisError::AssignmentMissing)
+        })?;
 
         // Witness allocation --------------------------------------------------
-        let witnesses: Vec<FpVar<F>> = random_inputs
-            .into_iter()
-            .map(|v| FpVar::new_witness(cs.clone(), || Ok(v)))
+        let witnesses: Vec<FpVar<F>> = (0..self.n as usize)
+            .map(|i| {
+                FpVar::new_witness(cs.clone(), || {
+                    inputs.map(|v| v[i]).ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
             .collect::<Result<Vec<FpVar<F>>, SynthesisError>>()?;
 
-        // Poseidon hash gadget ------------------------------------------------
-        let mut sponge = PoseidonSpongeVar::new(cs.clone(), &get_poseidon_config::<F>());
-        sponge.absorb(&witnesses)?;
-        let _hash = sponge.squeeze_field_elements(1)?[0].clone();
+        // Hash gadget ---------------------------------------------------------
+        let mut sponge = H::new(cs.clone());
+        sponge.absorb_many(witnesses.iter())?;
+        sponge.squeeze()?.enforce_equal(&digest)
+    }
+}
+
+/// Errors returned when building a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CircuitError {
+    /// More inputs than the circuit’s capacity.
+    TooManyInputs { capacity: u32, given: usize },
+    /// A declared range with `lo > hi`.
+    InvalidRange { field: usize, lo: u64, hi: u64 },
+    /// A report with a different number of fields than declared ranges.
+    FieldCount { expected: usize, given: usize },
+    /// A report field outside its declared range.
+    OutOfRange { field: usize, value: u64 },
+}
 
-        Ok(())
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyInputs { capacity, given } => {
+                write!(f, "{given} inputs exceed the capacity of {capacity}")
+            }
+            Self::InvalidRange { field, lo, hi } => {
+                write!(f, "field {field} declares the empty range [{lo}, {hi}]")
+            }
+            Self::FieldCount { expected, given } => {
+                write!(f, "{given} report fields for {expected} declared ranges")
+            }
+            Self::OutOfRange { field, value } => {
+                write!(f, "field {field} value {value} is outside its range")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+/// Circuit hashing a secret number `len ≤ capacity` of field elements:
+///
+/// ```text
+/// digest = H(len, x_0, …, x_{len−1}, 0, …, 0)      (capacity elements)
+/// ```
+///
+/// A prefix mask of `capacity` booleans encodes `len` (their sum); the
+/// mask is enforced monotone and every slot past `len` is enforced to be
+/// the padding value `0`. Absorbing `len` keeps `[a]` and `[a, 0]` apart,
+/// so one proving key covers every report size up to the capacity.
+#[derive(Clone)]
+pub struct VarLenPoseidonCircuit<F: PrimeField + Absorb, H: AlgebraicHashGadget<F> = PoseidonHashVar<F>> {
+    capacity: u32,
+    /// The preimage; `None` during setup.
+    inputs: Option<Vec<F>>,
+    _hash: PhantomData<fn() -> (F, H)>,
+}
+
+impl<F: PrimeField + Absorb, H: AlgebraicHashGadget<F>> VarLenPoseidonCircuit<F, H> {
+    /// Create a witness‑less circuit of the given capacity, for the setup.
+    pub fn new(capacity: u32) -> Self { Self { capacity, inputs: None, _hash: PhantomData } }
+
+    /// Create a circuit proving knowledge of `inputs`.
+    pub fn with_inputs(capacity: u32, inputs: Vec<F>) -> Result<Self, CircuitError> {
+        if inputs.len() > capacity as usize {
+            return Err(CircuitError::TooManyInputs { capacity, given: inputs.len() });
+        }
+        Ok(Self { capacity, inputs: Some(inputs), _hash: PhantomData })
+    }
+
+    /// Maximum number of hashed elements.
+    pub fn capacity(&self) -> u32 { self.capacity }
+
+    /// The preimage, if the circuit carries one.
+    pub fn inputs(&self) -> Option<&[F]> { self.inputs.as_deref() }
+
+    /// Digest of `inputs` padded to `capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inputs` is longer than `capacity`.
+    pub fn digest(capacity: u32, inputs: &[F]) -> F {
+        let padding = (capacity as usize)
+            .checked_sub(inputs.len())
+            .expect("inputs exceed the capacity");
+        let mut hash = H::Native::new();
+        hash.absorb_many([F::from(inputs.len() as u64)]);
+        hash.absorb_many(inputs);
+        hash.absorb_many(std::iter::repeat_n(F::zero(), padding));
+        hash.squeeze()
+    }
+
+    /// Public inputs a verifier checks a proof over `inputs` against.
+    pub fn public_inputs(capacity: u32, inputs: &[F]) -> Vec<F> {
+        vec![Self::digest(capacity, inputs)]
+    }
+}
+
+impl<F: PrimeField + Absorb, H: AlgebraicHashGadget<F>> ConstraintSynthesizer<F> for VarLenPoseidonCircuit<F, H> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let inputs = self.inputs.as_deref();
+        let capacity = self.capacity as usize;
+
+        // Public digest -------------------------------------------------------
+        let digest = FpVar::new_input(cs.clone(), || {
+            inputs
+                .map(|v| Self::digest(self.capacity, v))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Length mask: active[i] ⇔ i < len ----------------------------------
+        let active = (0..capacity)
+            .map(|i| {
+                Boolean::new_witness(cs.clone(), || {
+                    inputs.map(|v| i < v.len()).ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let active: Vec<FpVar<F>> = active.into_iter().map(FpVar::from).collect();
+        for pair in active.windows(2) {
+            // active[i + 1] ⇒ active[i]
+            pair[1].mul_equals(&(FpVar::one() - &pair[0]), &FpVar::zero())?;
+        }
+        let len = active.iter().fold(FpVar::zero(), |acc, a| acc + a);
+
+        // Padded witnesses ----------------------------------------------------
+        let witnesses: Vec<FpVar<F>> = (0..capacity)
+            .map(|i| {
+                FpVar::new_witness(cs.clone(), || {
+                    inputs
+                        .map(|v| v.get(i).copied().unwrap_or(F::zero()))
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect::<Result<Vec<FpVar<F>>, SynthesisError>>()?;
+        for (x, a) in witnesses.iter().zip(&active) {
+            // inactive slots hold the padding value 0
+            x.mul_equals(&(FpVar::one() - a), &FpVar::zero())?;
+        }
+
+        // Hash gadget ---------------------------------------------------------
+        let mut sponge = H::new(cs.clone());
+        sponge.absorb_many([&len])?;
+        sponge.absorb_many(witnesses.iter())?;
+        sponge.squeeze()?.enforce_equal(&digest)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_bn254::Fr;
+    use crate::hash::{AnemoiHashVar, RescuePrimeHashVar};
+    use ark_bn254::{Bn254, Fr};
+    use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+    use ark_groth16::Groth16;
     use ark_relations::r1cs::ConstraintSystem;
 
     #[test]
     fn circuit_satisfies() {
         let cs = ConstraintSystem::new_ref();
-        PoseidonCircuit::<Fr>::new(10).generate_constraints(cs.clone()).unwrap();
+        let circuit = PoseidonCircuit::<Fr>::random(10);
+        let public = PoseidonCircuit::<Fr>::public_inputs(circuit.inputs().unwrap());
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], public[..]);
+
+        let cs = ConstraintSystem::new_ref();
+        Poseidon2Circuit::<Fr>::random(10).generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn circuit_generic_over_hash() {
+        fn constraints<H: AlgebraicHashGadget<Fr>>() -> usize {
+            let cs = ConstraintSystem::new_ref();
+            PoseidonCircuit::<Fr, H>::random(16).generate_constraints(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            cs.num_constraints()
+        }
+
+        let poseidon = constraints::<PoseidonHashVar<Fr>>();
+        let rescue = constraints::<RescuePrimeHashVar<Fr>>();
+        let anemoi = constraints::<AnemoiHashVar<Fr>>();
+        assert!(anemoi < poseidon && anemoi < rescue);
+    }
+
+    #[test]
+    fn proof_binds_the_digest() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // one setup without witnesses, proofs over arbitrary reports
+        let (pk, vk) = Groth16::<Bn254>::setup(PoseidonCircuit::<Fr>::new(4), &mut rng).unwrap();
+
+        for report in [[1u64, 2, 3, 4], [7, 0, 0, 9]] {
+            let report: Vec<Fr> = report.into_iter().map(Fr::from).collect();
+            let circuit = PoseidonCircuit::<Fr>::with_inputs(report.clone());
+            let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+
+            let public = PoseidonCircuit::<Fr>::public_inputs(&report);
+            assert!(Groth16::<Bn254>::verify(&vk, &public, &proof).unwrap());
+            assert!(!Groth16::<Bn254>::verify(&vk, &[public[0] + Fr::from(1u64)], &proof).unwrap());
+        }
+
+        // a witness‑less circuit cannot be proven
+        let cs = ConstraintSystem::new_ref();
+        assert_eq!(
+            PoseidonCircuit::<Fr>::new(4).generate_constraints(cs),
+            Err(SynthesisError::AssignmentMissing)
+        );
+    }
+
+    #[test]
+    fn one_setup_covers_every_length() {
+        type Circuit = VarLenPoseidonCircuit<Fr>;
+        let mut rng = StdRng::seed_from_u64(7);
+        let (pk, vk) = Groth16::<Bn254>::setup(Circuit::new(6), &mut rng).unwrap();
+
+        for len in [0u64, 1, 3, 6] {
+            let report: Vec<Fr> = (1..=len).map(Fr::from).collect();
+            let circuit = Circuit::with_inputs(6, report.clone()).unwrap();
+            let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+            let public = Circuit::public_inputs(6, &report);
+            assert!(Groth16::<Bn254>::verify(&vk, &public, &proof).unwrap());
+        }
+
+        // the length is bound: explicit zeros do not collide with padding
+        let one = [Fr::from(5u64)];
+        let padded = [Fr::from(5u64), Fr::from(0u64)];
+        assert_ne!(Circuit::digest(6, &one), Circuit::digest(6, &padded));
+        assert_eq!(
+            Circuit::with_inputs(2, vec![Fr::from(1u64); 3]).err(),
+            Some(CircuitError::TooManyInputs { capacity: 2, given: 3 })
+        );
+    }
+
+    #[test]
+    fn padding_slots_must_be_zero() {
+        let circuit = VarLenPoseidonCircuit::<Fr>::with_inputs(4, vec![Fr::from(9u64)]).unwrap();
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
         assert!(cs.is_satisfied().unwrap());
+
+        // witnesses: 4 mask bits, then 4 slots; fill slot 2 past len = 1
+        cs.borrow_mut().unwrap().witness_assignment[4 + 2] = Fr::from(1u64);
+        assert!(!cs.is_satisfied().unwrap());
+        // a gap in the mask is rejected as well
+        let cs = ConstraintSystem::new_ref();
+        VarLenPoseidonCircuit::<Fr>::with_inputs(4, vec![Fr::from(9u64)])
+            .unwrap()
+            .generate_constraints(cs.clone())
+            .unwrap();
+        cs.borrow_mut().unwrap().witness_assignment[2] = Fr::from(1u64);
+        assert!(!cs.is_satisfied().unwrap());
     }
 }
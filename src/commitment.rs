@@ -0,0 +1,122 @@
+//! Hiding Poseidon commitments.
+//!
+//! A commitment to `values` under a random `blinding` is
+//!
+//! ```text
+//! C = H_commit(blinding, len, values…)
+//! ```
+//!
+//! with [`PoseidonHash`] in the `"commitment"` domain. It is hiding as
+//! long as `blinding` is uniformly random and binding because Poseidon is
+//! collision resistant; the length is absorbed so that `[a]` and `[a, 0]`
+//! commit differently.
+//!
+//! A reporter publishes `C` ahead of time and later proves, with
+//! [`enforce_opening`], that the report inside a circuit is the one
+//! committed to, without revealing it.
+//!
+//! [`PedersenParams`] provides additively homomorphic commitments on the
+//! twisted Edwards curve embedded in each pairing curve ([`edwards`]),
+//! for values that are summed outside the circuit.
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{R1CSVar, eq::EqGadget, fields::FieldVar, fields::fp::FpVar};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::hash::{PoseidonHash, PoseidonHashVar, domain_tag};
+
+pub mod edwards;
+pub mod pedersen;
+
+pub use edwards::EmbeddedCurve;
+pub use pedersen::{
+    PedersenCommitment, PedersenCommitmentVar, PedersenError, PedersenOpening, PedersenOpeningVar,
+    PedersenParams,
+};
+
+/// Commit to `values` under `blinding`.
+pub fn commit<F: Absorb + PrimeField>(values: &[F], blinding: &F) -> F {
+    let mut hash = PoseidonHash::with_domain(domain_tag(b"commitment"));
+    hash.absorb_many([*blinding, F::from(values.len() as u64)]);
+    hash.absorb_many(values);
+    hash.squeeze()
+}
+
+/// Whether `(values, blinding)` opens `commitment`.
+pub fn open<F: Absorb + PrimeField>(commitment: &F, values: &[F], blinding: &F) -> bool {
+    commit(values, blinding) == *commitment
+}
+
+/// In‑circuit [`commit`]; the number of values is a constant.
+pub fn commit_var<F: Absorb + PrimeField>(
+    values: &[FpVar<F>],
+    blinding: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let cs = values
+        .iter()
+        .fold(blinding.cs(), |cs, value| cs.or(value.cs()));
+    let mut hash = PoseidonHashVar::with_domain(cs, domain_tag(b"commitment"));
+    let len = FpVar::constant(F::from(values.len() as u64));
+    hash.try_absorb_many([blinding, &len])?;
+    hash.try_absorb_many(values)?;
+    hash.try_squeeze()
+}
+
+/// Enforce that `(values, blinding)` opens `commitment`, typically a
+/// public input opened with private values.
+pub fn enforce_opening<F: Absorb + PrimeField>(
+    commitment: &FpVar<F>,
+    values: &[FpVar<F>],
+    blinding: &FpVar<F>,
+) -> Result<(), SynthesisError> {
+    commit_var(values, blinding)?.enforce_equal(commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::alloc::AllocVar;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn commitments_open_and_bind() {
+        let mut rng = ark_std::test_rng();
+        let report: Vec<Fr> = (1u64..=4).map(Fr::from).collect();
+        let blinding = Fr::rand(&mut rng);
+        let c = commit(&report, &blinding);
+
+        assert!(open(&c, &report, &blinding));
+        assert!(!open(&c, &report, &Fr::rand(&mut rng)));
+        assert!(!open(&c, &report[..3], &blinding));
+        assert_ne!(commit(&report, &Fr::rand(&mut rng)), c);
+
+        let one = [Fr::from(1u64)];
+        let padded = [Fr::from(1u64), Fr::from(0u64)];
+        assert_ne!(commit(&one, &blinding), commit(&padded, &blinding));
+    }
+
+    #[test]
+    fn gadget_opens_public_commitment() {
+        let mut rng = ark_std::test_rng();
+        let report: Vec<Fr> = (1u64..=4).map(Fr::from).collect();
+        let blinding = Fr::rand(&mut rng);
+        let c = commit(&report, &blinding);
+
+        let prove = |values: &[Fr]| {
+            let cs = ConstraintSystem::new_ref();
+            let c_var = FpVar::new_input(cs.clone(), || Ok(c)).unwrap();
+            let values = Vec::new_witness(cs.clone(), || Ok(values.to_vec())).unwrap();
+            let blinding = FpVar::new_witness(cs.clone(), || Ok(blinding)).unwrap();
+            enforce_opening(&c_var, &values, &blinding).unwrap();
+            assert_eq!(cs.num_instance_variables(), 2);
+            cs.is_satisfied().unwrap()
+        };
+        assert!(prove(&report));
+        let mut tampered = report.clone();
+        tampered[2] += Fr::from(1u64);
+        assert!(!prove(&tampered));
+    }
+}
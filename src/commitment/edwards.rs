@@ -0,0 +1,66 @@
+//! Twisted Edwards curves embedded in the supported pairing curves.
+//!
+//! Each curve is defined over the scalar field of a pairing curve, so its
+//! points are native in circuits over that field:
+//!
+//! * BabyJubJub ([`ark_ed_on_bn254`]) over BN254 `Fr`;
+//! * Jubjub ([`ark_ed_on_bls12_381`]) over BLS12‑381 `Fr`;
+//! * [`ark_ed_on_bls12_377`] over BLS12‑377 `Fr`.
+//!
+//! [`EmbeddedCurve`] picks the curve from the circuit field.
+
+use ark_ec::twisted_edwards::TECurveConfig;
+use ark_ff::PrimeField;
+
+/// Circuit fields that have an embedded twisted Edwards curve.
+pub trait EmbeddedCurve: PrimeField {
+    /// The curve whose base field is `Self`.
+    type Config: TECurveConfig<BaseField = Self>;
+}
+
+impl EmbeddedCurve for ark_bn254::Fr {
+    type Config = ark_ed_on_bn254::EdwardsConfig;
+}
+
+impl EmbeddedCurve for ark_bls12_381::Fr {
+    type Config = ark_ed_on_bls12_381::EdwardsConfig;
+}
+
+impl EmbeddedCurve for ark_bls12_377::Fr {
+    type Config = ark_ed_on_bls12_377::EdwardsConfig;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::twisted_edwards::Affine;
+    use ark_ec::{AffineRepr, CurveConfig, CurveGroup};
+    use ark_ff::{Field, UniformRand};
+
+    type Scalar<F> = <<F as EmbeddedCurve>::Config as CurveConfig>::ScalarField;
+
+    fn check_curve<F: EmbeddedCurve>() {
+        let g = Affine::<F::Config>::generator();
+        assert!(g.is_on_curve() && g.is_in_correct_subgroup_assuming_on_curve());
+        assert!(!g.is_zero());
+        let cofactor = Scalar::<F>::from(F::Config::COFACTOR[0]);
+        assert_eq!(F::Config::COFACTOR_INV * cofactor, Scalar::<F>::ONE);
+
+        let mut rng = ark_std::test_rng();
+        let (a, b) = (Scalar::<F>::rand(&mut rng), Scalar::<F>::rand(&mut rng));
+        let g = g.into_group();
+        assert_eq!(g * a + g * b, g * (a + b));
+        assert!(
+            (g * a)
+                .into_affine()
+                .is_in_correct_subgroup_assuming_on_curve()
+        );
+    }
+
+    #[test]
+    fn embedded_curves_are_well_formed() {
+        check_curve::<ark_bn254::Fr>();
+        check_curve::<ark_bls12_381::Fr>();
+        check_curve::<ark_bls12_377::Fr>();
+    }
+}
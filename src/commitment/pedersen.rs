@@ -0,0 +1,369 @@
+//! Pedersen vector commitments on an embedded twisted Edwards curve.
+//!
+//! A commitment to `values` (scalars of the curve) under a random
+//! `blinding` is
+//!
+//! ```text
+//! C = Σ values[i]·G[i] + blinding·H
+//! ```
+//!
+//! which is perfectly hiding, computationally binding and additively
+//! homomorphic: `C(v, r) + C(v′, r′) = C(v + v′, r + r′)`, so committed
+//! report values can be summed outside the circuit and the sum opened
+//! once.
+//!
+//! The generators are derived by hashing (SHA3‑256, try‑and‑increment on
+//! `y`, then clearing the cofactor), so nobody knows a discrete‑log
+//! relation between them. The curve is embedded in the circuit field
+//! (see [`edwards`](super::edwards)); the opening gadget takes the
+//! values and blinding as little‑endian scalar bits and uses fixed‑base
+//! multiplication against precomputed powers of two.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+
+use ark_ec::twisted_edwards::{Affine, Projective, TECurveConfig};
+use ark_ec::{AffineRepr, CurveConfig, CurveGroup, VariableBaseMSM};
+use ark_ff::{AdditiveGroup, BigInteger, PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    groups::{CurveVar, curves::twisted_edwards::AffineVar},
+};
+use ark_relations::r1cs::{Namespace, SynthesisError};
+
+use crate::digest::sha3_256;
+
+type Scalar<P> = <P as CurveConfig>::ScalarField;
+
+/// In‑circuit Pedersen commitment (a curve point over the circuit field).
+pub type PedersenCommitmentVar<P> = AffineVar<P, FpVar<<P as CurveConfig>::BaseField>>;
+
+/// Errors returned by Pedersen operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PedersenError {
+    /// More values than the parameters have generators.
+    TooManyValues { capacity: usize, given: usize },
+}
+
+impl fmt::Display for PedersenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyValues { capacity, given } => {
+                write!(f, "{given} values exceed the {capacity} generators")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PedersenError {}
+
+/// Hash `label ‖ index` to a point of the prime‑order subgroup.
+fn hash_to_curve<P: TECurveConfig>(label: &[u8], index: u64) -> Affine<P>
+where
+    P::BaseField: PrimeField,
+{
+    (0u64..)
+        .find_map(|counter| {
+            let mut input = b"zk-reporting pedersen ".to_vec();
+            input.extend(label);
+            input.extend(index.to_le_bytes());
+            input.extend(counter.to_le_bytes());
+            let y = P::BaseField::from_le_bytes_mod_order(&sha3_256(&input));
+            let point = Affine::<P>::get_point_from_y_unchecked(y, false)?.mul_by_cofactor();
+            (!point.is_zero()).then_some(point)
+        })
+        .expect("try-and-increment terminates")
+}
+
+/// Generators for commitments to up to `generators.len()` values.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PedersenParams<P: TECurveConfig> {
+    /// One generator per value.
+    pub generators: Vec<Affine<P>>,
+    /// Generator of the blinding term.
+    pub blinding_generator: Affine<P>,
+}
+
+// curve configs are marker types that need not implement `Debug`
+impl<P: TECurveConfig> fmt::Debug for PedersenParams<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PedersenParams")
+            .field("generators", &self.generators)
+            .field("blinding_generator", &self.blinding_generator)
+            .finish()
+    }
+}
+
+impl<P: TECurveConfig> PedersenParams<P>
+where
+    P::BaseField: PrimeField,
+{
+    /// Deterministic parameters for vectors of up to `n` values.
+    pub fn new(n: usize) -> Self {
+        Self {
+            generators: (0..n as u64).map(|i| hash_to_curve(b"value", i)).collect(),
+            blinding_generator: hash_to_curve(b"blinding", 0),
+        }
+    }
+
+    /// Commit to `values` under `blinding`.
+    pub fn commit(
+        &self,
+        values: &[Scalar<P>],
+        blinding: &Scalar<P>,
+    ) -> Result<PedersenCommitment<P>, PedersenError> {
+        if values.len() > self.generators.len() {
+            return Err(PedersenError::TooManyValues {
+                capacity: self.generators.len(),
+                given: values.len(),
+            });
+        }
+        let c = Projective::<P>::msm_unchecked(&self.generators[..values.len()], values)
+            + self.blinding_generator * blinding;
+        Ok(PedersenCommitment(c.into_affine()))
+    }
+
+    /// Whether `(values, blinding)` opens `commitment`.
+    pub fn open(
+        &self,
+        commitment: &PedersenCommitment<P>,
+        values: &[Scalar<P>],
+        blinding: &Scalar<P>,
+    ) -> bool {
+        self.commit(values, blinding)
+            .is_ok_and(|c| c.0 == commitment.0)
+    }
+}
+
+/// A Pedersen commitment; commitments add like the values they hide.
+#[derive(PartialEq, Eq)]
+pub struct PedersenCommitment<P: TECurveConfig>(pub Affine<P>);
+
+impl<P: TECurveConfig> Clone for PedersenCommitment<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: TECurveConfig> Copy for PedersenCommitment<P> {}
+
+impl<P: TECurveConfig> fmt::Debug for PedersenCommitment<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PedersenCommitment").field(&self.0).finish()
+    }
+}
+
+impl<P: TECurveConfig> Add for PedersenCommitment<P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self((self.0 + other.0).into_affine())
+    }
+}
+
+impl<P: TECurveConfig> Sum for PedersenCommitment<P> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self(
+            iter.map(|c| c.0.into_group())
+                .sum::<Projective<P>>()
+                .into_affine(),
+        )
+    }
+}
+
+/// Values and blinding opening a [`PedersenCommitment`]; openings add
+/// component‑wise, matching the sum of their commitments.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PedersenOpening<P: TECurveConfig> {
+    /// Committed values.
+    pub values: Vec<Scalar<P>>,
+    /// Blinding factor.
+    pub blinding: Scalar<P>,
+}
+
+impl<P: TECurveConfig> fmt::Debug for PedersenOpening<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PedersenOpening")
+            .field("values", &self.values)
+            .field("blinding", &self.blinding)
+            .finish()
+    }
+}
+
+impl<P: TECurveConfig> Add for PedersenOpening<P> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        if self.values.len() < other.values.len() {
+            self.values.resize(other.values.len(), Scalar::<P>::zero());
+        }
+        for (v, w) in self.values.iter_mut().zip(other.values) {
+            *v += w;
+        }
+        self.blinding += other.blinding;
+        self
+    }
+}
+
+/// In‑circuit [`PedersenOpening`]: little‑endian bits of each scalar.
+#[derive(Clone)]
+pub struct PedersenOpeningVar<P: TECurveConfig>
+where
+    P::BaseField: PrimeField,
+{
+    /// Bits of each committed value.
+    pub values: Vec<Vec<Boolean<P::BaseField>>>,
+    /// Bits of the blinding factor.
+    pub blinding: Vec<Boolean<P::BaseField>>,
+}
+
+fn scalar_bits<P: TECurveConfig>(scalar: &Scalar<P>) -> Vec<bool> {
+    let mut bits = scalar.into_bigint().to_bits_le();
+    bits.truncate(Scalar::<P>::MODULUS_BIT_SIZE as usize);
+    bits
+}
+
+impl<P: TECurveConfig> AllocVar<PedersenOpening<P>, P::BaseField> for PedersenOpeningVar<P>
+where
+    P::BaseField: PrimeField,
+{
+    fn new_variable<T: Borrow<PedersenOpening<P>>>(
+        cs: impl Into<Namespace<P::BaseField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let opening = f()?;
+        let opening = opening.borrow();
+        let values = opening
+            .values
+            .iter()
+            .map(|v| Vec::new_variable(cs.clone(), || Ok(scalar_bits::<P>(v)), mode))
+            .collect::<Result<_, _>>()?;
+        let blinding = Vec::new_variable(cs, || Ok(scalar_bits::<P>(&opening.blinding)), mode)?;
+        Ok(Self { values, blinding })
+    }
+}
+
+impl<P: TECurveConfig> PedersenParams<P>
+where
+    P::BaseField: PrimeField,
+{
+    /// In‑circuit [`Self::commit`].
+    pub fn commit_var(
+        &self,
+        opening: &PedersenOpeningVar<P>,
+    ) -> Result<PedersenCommitmentVar<P>, SynthesisError> {
+        if opening.values.len() > self.generators.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut c = PedersenCommitmentVar::<P>::zero();
+        let terms = opening
+            .values
+            .iter()
+            .zip(&self.generators)
+            .chain([(&opening.blinding, &self.blinding_generator)]);
+        for (bits, generator) in terms {
+            let powers: Vec<Projective<P>> =
+                std::iter::successors(Some(generator.into_group()), |g| Some(g.double()))
+                    .take(bits.len())
+                    .collect();
+            c.precomputed_base_scalar_mul_le(bits.iter().zip(&powers))?;
+        }
+        Ok(c)
+    }
+
+    /// Enforce that `opening` opens `commitment`, typically a public
+    /// input opened with private bits.
+    pub fn enforce_opening(
+        &self,
+        commitment: &PedersenCommitmentVar<P>,
+        opening: &PedersenOpeningVar<P>,
+    ) -> Result<(), SynthesisError> {
+        self.commit_var(opening)?.enforce_equal(commitment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ed_on_bn254::{EdwardsConfig, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn commitments_add_homomorphically() {
+        let mut rng = ark_std::test_rng();
+        let params = PedersenParams::<EdwardsConfig>::new(3);
+        assert!(
+            params
+                .generators
+                .iter()
+                .all(|g| { g.is_on_curve() && g.is_in_correct_subgroup_assuming_on_curve() })
+        );
+        assert_ne!(params.generators[0], params.generators[1]);
+
+        let reports: Vec<PedersenOpening<EdwardsConfig>> = (0u64..4)
+            .map(|i| PedersenOpening {
+                values: vec![Fr::from(i + 1), Fr::from(10 * i)],
+                blinding: Fr::rand(&mut rng),
+            })
+            .collect();
+        let commitments: Vec<_> = reports
+            .iter()
+            .map(|o| params.commit(&o.values, &o.blinding).unwrap())
+            .collect();
+
+        let total = reports.iter().cloned().reduce(|a, b| a + b).unwrap();
+        assert_eq!(total.values, vec![Fr::from(10u64), Fr::from(60u64)]);
+        let sum: PedersenCommitment<_> = commitments.iter().copied().sum();
+        assert!(params.open(&sum, &total.values, &total.blinding));
+        assert_eq!(
+            commitments[0] + commitments[1],
+            params
+                .commit(
+                    &[Fr::from(3u64), Fr::from(10u64)],
+                    &(reports[0].blinding + reports[1].blinding),
+                )
+                .unwrap()
+        );
+        assert!(!params.open(&sum, &total.values, &Fr::from(0u64)));
+
+        assert_eq!(
+            params.commit(&[Fr::from(1u64); 4], &Fr::from(0u64)),
+            Err(PedersenError::TooManyValues {
+                capacity: 3,
+                given: 4
+            })
+        );
+    }
+
+    #[test]
+    fn gadget_opens_public_commitment() {
+        let mut rng = ark_std::test_rng();
+        let params = PedersenParams::<EdwardsConfig>::new(2);
+        let opening = PedersenOpening {
+            values: vec![Fr::from(42u64), Fr::rand(&mut rng)],
+            blinding: Fr::rand(&mut rng),
+        };
+        let c = params.commit(&opening.values, &opening.blinding).unwrap();
+
+        let prove = |opening: &PedersenOpening<EdwardsConfig>| {
+            let cs = ConstraintSystem::new_ref();
+            let c_var = PedersenCommitmentVar::new_input(cs.clone(), || Ok(c.0)).unwrap();
+            let opening = PedersenOpeningVar::new_witness(cs.clone(), || Ok(opening)).unwrap();
+            params.enforce_opening(&c_var, &opening).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        assert!(prove(&opening));
+        let mut tampered = opening.clone();
+        tampered.values[0] += Fr::from(1u64);
+        assert!(!prove(&tampered));
+    }
+}
@@ -0,0 +1,53 @@
+//! Byte‑level digests used outside the algebraic hashes.
+//!
+//! SHA3‑256 derives the SAFE domain tags, fingerprints sponge
+//! checkpoints, checksums the Merkle store log and seeds Pedersen
+//! hash‑to‑curve.
+
+use sha3::{Digest, Sha3_256};
+
+/// SHA3‑256 (FIPS 202) of `data`.
+pub(crate) fn sha3_256(data: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn sha3_matches_fips_202() {
+        assert_eq!(
+            hex(&sha3_256(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex(&sha3_256(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            hex(&sha3_256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376"
+        );
+        // two blocks
+        assert_eq!(
+            hex(&sha3_256(&[0xa3; 200])),
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787"
+        );
+        // padding in a single byte (0x86), then in a block of its own
+        assert_eq!(
+            hex(&sha3_256(&[b'a'; 135])),
+            "8094bb53c44cfb1e67b7c30447f9a1c33696d2463ecc1d9c92538913392843c9"
+        );
+        assert_eq!(
+            hex(&sha3_256(&[b'a'; 136])),
+            "3fc5559f14db8e453a0a3091edbd2bc25e11528d81c66fa570a4efdcc2695ee1"
+        );
+    }
+}
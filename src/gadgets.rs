@@ -0,0 +1,254 @@
+//! Comparison and range gadgets over [`FpVar`].
+//!
+//! Everything here except [`is_equal`] (the inverse‑based
+//! [`EqGadget::is_eq`]) is built on [`to_bits_le`], a lookup‑free
+//! decomposition of a field element into `k` witness bits: `k`
+//! booleanity constraints plus one linear constraint recombining them.
+//! A value decomposes into `k` bits iff it is below `2^k`, provided `k`
+//! is smaller than the field’s bit size (otherwise the decomposition is
+//! not unique); wider requests are rejected with
+//! [`SynthesisError::Unsatisfiable`].
+//!
+//! | gadget                        | variable inputs | all inputs constant |
+//! |-------------------------------|-----------------|---------------------|
+//! | [`to_bits_le`]                | `k + 1`         | `0`                 |
+//! | [`enforce_bit_length`]        | `k + 1`         | `0`                 |
+//! | [`enforce_signed_bit_length`] | `k + 1`         | `0`                 |
+//! | [`enforce_in_range`]          | `2b + 2`        | `0`                 |
+//! | [`enforce_signed_in_range`]   | `2b + 2`        | `0`                 |
+//! | [`is_less_than`]              | `k + 2`         | `0`                 |
+//! | [`is_equal`]                  | `2`             | `0`                 |
+//! | [`min`] / [`max`]             | `k + 3`         | `0`                 |
+//!
+//! where `b` is the bit length of `hi − lo`. When every input is a
+//! constant the check runs natively, and a violated bound is reported as
+//! [`SynthesisError::Unsatisfiable`]. Signed values are represented the
+//! usual way, `−v` as `p − v`.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{R1CSVar, alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar};
+use ark_relations::r1cs::SynthesisError;
+
+/// `2^k` in `F`.
+fn power_of_two<F: PrimeField>(k: usize) -> F {
+    F::from(2u64).pow([k as u64])
+}
+
+/// Decompose `x` into `k` little‑endian bits, enforcing `x < 2^k`.
+pub fn to_bits_le<F: PrimeField>(
+    x: &FpVar<F>,
+    k: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    if k >= F::MODULUS_BIT_SIZE as usize {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    if let FpVar::Constant(c) = x {
+        let bits = c.into_bigint().to_bits_le();
+        if bits[k..].iter().any(|&b| b) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        return Ok(bits[..k].iter().map(|&b| Boolean::constant(b)).collect());
+    }
+    let bits = (0..k)
+        .map(|i| Boolean::new_witness(x.cs(), || Ok(x.value()?.into_bigint().get_bit(i))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp(&bits)?.enforce_equal(x)?;
+    Ok(bits)
+}
+
+/// Enforce `0 ≤ x < 2^k`.
+pub fn enforce_bit_length<F: PrimeField>(x: &FpVar<F>, k: usize) -> Result<(), SynthesisError> {
+    to_bits_le(x, k).map(drop)
+}
+
+/// Enforce `−2^(k−1) ≤ x < 2^(k−1)`, i.e. that `x` is a `k`‑bit signed
+/// integer.
+pub fn enforce_signed_bit_length<F: PrimeField>(
+    x: &FpVar<F>,
+    k: usize,
+) -> Result<(), SynthesisError> {
+    let half = k.checked_sub(1).ok_or(SynthesisError::Unsatisfiable)?;
+    enforce_bit_length(&(x + power_of_two::<F>(half)), k)
+}
+
+/// Enforce `lo ≤ x ≤ hi` given `width = hi − lo` as an integer.
+fn enforce_between<F: PrimeField>(
+    x: &FpVar<F>,
+    lo: F,
+    hi: F,
+    width: u64,
+) -> Result<(), SynthesisError> {
+    // both sides are below 2^b ≤ 2^64; out of range, one of them wraps
+    // around to at least p − 2^64
+    let b = (u64::BITS - width.leading_zeros()) as usize;
+    enforce_bit_length(&(x - lo), b)?;
+    enforce_bit_length(&(FpVar::Constant(hi) - x), b)
+}
+
+/// Enforce `lo ≤ x ≤ hi`.
+pub fn enforce_in_range<F: PrimeField>(
+    x: &FpVar<F>,
+    lo: u64,
+    hi: u64,
+) -> Result<(), SynthesisError> {
+    let width = hi.checked_sub(lo).ok_or(SynthesisError::Unsatisfiable)?;
+    enforce_between(x, F::from(lo), F::from(hi), width)
+}
+
+/// Enforce `lo ≤ x ≤ hi` for signed bounds.
+pub fn enforce_signed_in_range<F: PrimeField>(
+    x: &FpVar<F>,
+    lo: i64,
+    hi: i64,
+) -> Result<(), SynthesisError> {
+    if lo > hi {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    enforce_between(x, F::from(lo), F::from(hi), hi.abs_diff(lo))
+}
+
+/// Whether `a < b`, for `a` and `b` already known to be below `2^k`.
+///
+/// `2^k + a − b` lies in `(0, 2^(k+1))` and reaches `2^k` iff `a ≥ b`,
+/// so the answer is the negated top bit of its `(k + 1)`‑bit
+/// decomposition.
+pub fn is_less_than<F: PrimeField>(
+    a: &FpVar<F>,
+    b: &FpVar<F>,
+    k: usize,
+) -> Result<Boolean<F>, SynthesisError> {
+    let shifted = a - b + power_of_two::<F>(k);
+    let bits = to_bits_le(&shifted, k + 1)?;
+    Ok(!&bits[k])
+}
+
+/// Whether `a = b`.
+pub fn is_equal<F: PrimeField>(a: &FpVar<F>, b: &FpVar<F>) -> Result<Boolean<F>, SynthesisError> {
+    a.is_eq(b)
+}
+
+/// The smaller of `a` and `b`, both below `2^k`.
+pub fn min<F: PrimeField>(
+    a: &FpVar<F>,
+    b: &FpVar<F>,
+    k: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    is_less_than(a, b, k)?.select(a, b)
+}
+
+/// The larger of `a` and `b`, both below `2^k`.
+pub fn max<F: PrimeField>(
+    a: &FpVar<F>,
+    b: &FpVar<F>,
+    k: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    is_less_than(a, b, k)?.select(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef};
+
+    /// Run `gadget` on witnesses `values`, returning whether the system is
+    /// satisfied and how many constraints the gadget added.
+    fn run<T>(
+        values: &[Fr],
+        gadget: impl FnOnce(&[FpVar<Fr>]) -> Result<T, SynthesisError>,
+    ) -> (T, bool, usize) {
+        let cs: ConstraintSystemRef<Fr> = ConstraintSystem::new_ref();
+        let vars = Vec::new_witness(cs.clone(), || Ok(values.to_vec())).unwrap();
+        let out = gadget(&vars).unwrap();
+        (out, cs.is_satisfied().unwrap(), cs.num_constraints())
+    }
+
+    #[test]
+    fn decomposition_and_bit_length() {
+        let (bits, ok, n) = run(&[Fr::from(0b1011u64)], |x| to_bits_le(&x[0], 6));
+        let bits: Vec<bool> = bits.iter().map(|b| b.value().unwrap()).collect();
+        assert_eq!(bits, [true, true, false, true, false, false]);
+        assert!(ok);
+        assert_eq!(n, 7);
+
+        for (value, ok) in [(255u64, true), (256, false)] {
+            let (_, sat, n) = run(&[Fr::from(value)], |x| enforce_bit_length(&x[0], 8));
+            assert_eq!((sat, n), (ok, 9));
+        }
+        let (_, sat, _) = run(&[-Fr::from(1u64)], |x| enforce_bit_length(&x[0], 8));
+        assert!(!sat);
+
+        // constants are checked natively, too wide a decomposition is refused
+        let c = FpVar::Constant(Fr::from(300u64));
+        assert!(enforce_bit_length(&c, 9).is_ok());
+        let (a, b) = (FpVar::Constant(Fr::from(3u64)), c.clone());
+        assert!(is_less_than(&a, &b, 9).unwrap().value().unwrap());
+        assert_eq!(max(&a, &b, 9).unwrap().value().unwrap(), Fr::from(300u64));
+        assert!(!is_equal(&a, &b).unwrap().value().unwrap());
+        assert!(enforce_signed_in_range(&a, -4, 4).is_ok());
+        assert_eq!(
+            enforce_bit_length(&c, 8),
+            Err(SynthesisError::Unsatisfiable)
+        );
+        assert_eq!(
+            to_bits_le(&c, 254).err(),
+            Some(SynthesisError::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn signed_ranges() {
+        for (value, ok) in [(-128i64, true), (127, true), (-129, false), (128, false)] {
+            let (_, sat, n) = run(&[Fr::from(value)], |x| enforce_signed_bit_length(&x[0], 8));
+            assert_eq!((sat, n), (ok, 9));
+        }
+
+        // b = bit length of 60 − (−40) = 100, i.e. 7
+        for (value, ok) in [
+            (-40i64, true),
+            (0, true),
+            (60, true),
+            (-41, false),
+            (61, false),
+        ] {
+            let (_, sat, n) = run(&[Fr::from(value)], |x| {
+                enforce_signed_in_range(&x[0], -40, 60)
+            });
+            assert_eq!((sat, n), (ok, 16));
+        }
+        for (value, ok) in [(18u64, true), (65, true), (17, false), (66, false)] {
+            let (_, sat, n) = run(&[Fr::from(value)], |x| enforce_in_range(&x[0], 18, 65));
+            assert_eq!((sat, n), (ok, 14));
+        }
+        let x = FpVar::Constant(Fr::from(5u64));
+        assert_eq!(
+            enforce_in_range(&x, 9, 8),
+            Err(SynthesisError::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        for (a, b) in [(3u64, 9u64), (9, 3), (7, 7), (0, 255), (255, 0)] {
+            let values = [Fr::from(a), Fr::from(b)];
+            let (lt, ok, n) = run(&values, |v| is_less_than(&v[0], &v[1], 8));
+            assert_eq!(lt.value().unwrap(), a < b);
+            assert!(ok);
+            assert_eq!(n, 10);
+
+            let (eq, ok, n) = run(&values, |v| is_equal(&v[0], &v[1]));
+            assert_eq!(eq.value().unwrap(), a == b);
+            assert!(ok);
+            assert_eq!(n, 2);
+
+            let (lo, ok, n) = run(&values, |v| min(&v[0], &v[1], 8));
+            assert_eq!(lo.value().unwrap(), Fr::from(a.min(b)));
+            assert!(ok);
+            assert_eq!(n, 11);
+            let (hi, ok, n) = run(&values, |v| max(&v[0], &v[1], 8));
+            assert_eq!(hi.value().unwrap(), Fr::from(a.max(b)));
+            assert!(ok);
+            assert_eq!(n, 11);
+        }
+    }
+}
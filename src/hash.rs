@@ -5,25 +5,64 @@
 //! that mirrors the high‑level sponge operations while keeping
 //! the underlying parameters Circom‑compatible (4‑ary state,
 //! 120‑bit security).
+//!
+//! The [`circom`] submodule embeds circomlib’s BN254 table and
+//! reproduces its `Poseidon(4)` digest exactly. Poseidon2, Rescue‑Prime
+//! and Anemoi sponges live alongside, all behind the [`AlgebraicHash`]
+//! / [`AlgebraicHashGadget`] traits.
+//!
+//! Every sponge can be domain‑separated by writing a tag (see
+//! [`domain_tag`]) into its capacity element, so reports, nullifiers,
+//! Merkle nodes and commitments never share a hash domain.
 
 use ark_crypto_primitives::sponge::constraints::{AbsorbGadget, CryptographicSpongeVar};
 use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
 use ark_crypto_primitives::sponge::poseidon::{PoseidonSponge, find_poseidon_ark_and_mds};
 use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, poseidon::PoseidonConfig};
-use ark_ff::PrimeField;
-use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
-use ark_relations::r1cs::ConstraintSystemRef;
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    convert::ToConstraintFieldGadget,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::sync::Arc;
+
+pub mod anemoi;
+pub mod checkpoint;
+pub mod circom;
+pub mod params;
+pub mod poseidon2;
+pub mod registry;
+pub mod rescue;
+pub mod safe;
+pub mod sponge;
+pub mod traits;
+
+pub use anemoi::{AnemoiHash, AnemoiHashVar};
+pub use params::{PoseidonParamsBuilder, PoseidonParamsError};
+pub use poseidon2::{Poseidon2Hash, Poseidon2HashVar};
+pub use rescue::{RescuePrimeHash, RescuePrimeHashVar};
+pub use safe::{IoPattern, SafeError, SafeSponge, SafeSpongeVar};
+pub use traits::{AlgebraicHash, AlgebraicHashGadget};
+
+const FULL_ROUNDS: u64 = 8;
+const PARTIAL_ROUNDS: u64 = 60;
+const ALPHA: u64 = 5;
+const RATE: usize = 4; // t = rate + 1  ⇒ 5‑width state
 
 /// Returns a Poseidon configuration
-/// * identical to Circom’s `Poseidon(4)` when `F` is BN254’s scalar field;
-/// * targeting 120‑bit security as recommended in https://eprint.iacr.org/2019/458.pdf.
+/// * whose round constants and MDS equal circomlib’s `Poseidon(4)` table when `F` is
+///   BN254’s scalar field (see [`circom::poseidon_config`] for the embedded copy);
+/// * targeting 120‑bit security as recommended in <https://eprint.iacr.org/2019/458.pdf>.
+///
+/// Other widths, security levels and S‑boxes are available through
+/// [`PoseidonParamsBuilder`].
 #[inline]
 pub fn get_poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
-    const FULL_ROUNDS: u64 = 8;
-    const PARTIAL_ROUNDS: u64 = 60;
-    const ALPHA: u64 = 5;
-    const RATE: usize = 4; // t = rate + 1  ⇒ 5‑width state
-
     let (ark, mds) = find_poseidon_ark_and_mds::<F>(
         F::MODULUS_BIT_SIZE as u64,
         RATE,
@@ -43,6 +82,70 @@ pub fn get_poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
     )
 }
 
+/// Cached counterpart of [`get_poseidon_config`].
+///
+/// The configuration is generated once per field and shared through
+/// the [`registry`], so constructing sponges in a loop stays cheap.
+#[inline]
+pub fn cached_poseidon_config<F: PrimeField>() -> Arc<PoseidonConfig<F>> {
+    let key = registry::ParamsKey {
+        width: RATE + 1,
+        full_rounds: FULL_ROUNDS as usize,
+        partial_rounds: PARTIAL_ROUNDS as usize,
+        alpha: ALPHA,
+    };
+    registry::get_or_init(key, get_poseidon_config::<F>)
+}
+
+/// Encodes a short personalization string as a domain tag.
+///
+/// The bytes are read little‑endian and their length is stored in the
+/// most significant byte, so distinct strings (including ones differing
+/// only by trailing zero bytes) map to distinct tags below the modulus.
+/// Tags are never zero, the capacity of an untagged sponge.
+///
+/// # Panics
+///
+/// Panics if `personalization` is empty or longer than
+/// `(MODULUS_BIT_SIZE - 1) / 8 - 1` bytes (30 bytes for BN254 and BLS12
+/// scalar fields).
+pub fn domain_tag<F: PrimeField>(personalization: &[u8]) -> F {
+    let width = (F::MODULUS_BIT_SIZE as usize - 1) / 8;
+    assert!(!personalization.is_empty(), "empty personalization");
+    assert!(
+        personalization.len() < width,
+        "personalization longer than {} bytes",
+        width - 1
+    );
+    let mut bytes = vec![0u8; width];
+    bytes[..personalization.len()].copy_from_slice(personalization);
+    bytes[width - 1] = personalization.len() as u8;
+    F::from_le_bytes_mod_order(&bytes)
+}
+
+/// Packs bytes into field elements for absorption.
+///
+/// The packing is `[len, chunk_0, chunk_1, …]`: the byte length as a
+/// field element, followed by the bytes split into chunks of
+/// `(MODULUS_BIT_SIZE - 1) / 8` bytes (31 for BN254), each read
+/// little‑endian. Chunks stay below the modulus and the length prefix
+/// disambiguates zero padding, so the packing is injective.
+pub fn pack_bytes<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    let mut packed = vec![F::from(bytes.len() as u64)];
+    let chunks: Vec<F> = bytes
+        .to_field_elements()
+        .expect("chunks shorter than the modulus always deserialize");
+    packed.extend(chunks);
+    packed
+}
+
+/// In‑circuit [`pack_bytes`]; the length is a circuit constant.
+pub fn pack_bytes_var<F: PrimeField>(bytes: &[UInt8<F>]) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut packed = vec![FpVar::Constant(F::from(bytes.len() as u64))];
+    packed.extend(bytes.to_constraint_field()?);
+    Ok(packed)
+}
+
 /// Native‑field Poseidon hash helper.
 #[derive(Clone)]
 pub struct PoseidonHash<F: Absorb + PrimeField> {
@@ -52,13 +155,33 @@ pub struct PoseidonHash<F: Absorb + PrimeField> {
 impl<F: Absorb + PrimeField> PoseidonHash<F> {
     /// Construct a new sponge initialized with the canonical parameters.
     pub fn new() -> Self {
+        Self::with_config(&cached_poseidon_config::<F>())
+    }
+
+    /// Construct a new sponge over an explicit configuration.
+    pub fn with_config(config: &PoseidonConfig<F>) -> Self {
         Self {
-            sponge: PoseidonSponge::new(&get_poseidon_config::<F>()),
+            sponge: PoseidonSponge::new(config),
         }
     }
 
+    /// Construct a new sponge whose capacity element is set to `domain`.
+    ///
+    /// Sponges with distinct tags hash identical inputs to unrelated
+    /// outputs; tag `0` is the untagged sponge returned by [`Self::new`].
+    pub fn with_domain(domain: F) -> Self {
+        Self::with_config_and_domain(&cached_poseidon_config::<F>(), domain)
+    }
+
+    /// Construct a domain‑separated sponge over an explicit configuration.
+    pub fn with_config_and_domain(config: &PoseidonConfig<F>, domain: F) -> Self {
+        let mut hash = Self::with_config(config);
+        hash.sponge.state[0] = domain;
+        hash
+    }
+
     /// Absorb an iterator of field elements
-    pub fn absorb_many<I, A>(&mut self, iter: I) -> ()
+    pub fn absorb_many<I, A>(&mut self, iter: I)
     where
         I: IntoIterator<Item = A>,
         A: Absorb,
@@ -68,6 +191,16 @@ impl<F: Absorb + PrimeField> PoseidonHash<F> {
         }
     }
 
+    /// Absorb a byte string, packed with [`pack_bytes`].
+    pub fn absorb_bytes(&mut self, bytes: &[u8]) {
+        self.absorb_many(pack_bytes::<F>(bytes));
+    }
+
+    /// Absorb the UTF‑8 bytes of `s`; same as [`Self::absorb_bytes`].
+    pub fn absorb_str(&mut self, s: &str) {
+        self.absorb_bytes(s.as_bytes());
+    }
+
     /// Backwards‑compat alias (kept to avoid breaking external code).
     #[deprecated(note = "Use `absorb_many` instead")]
     pub fn update_sponge<A: Absorb>(&mut self, v: Vec<A>) {
@@ -79,9 +212,45 @@ impl<F: Absorb + PrimeField> PoseidonHash<F> {
         let squeezed_field_element: Vec<F> = self.sponge.squeeze_field_elements(1);
         squeezed_field_element[0]
     }
+
+    /// Squeeze `n` field elements (e.g. several challenges at once).
+    pub fn squeeze_n(&mut self, n: usize) -> Vec<F> {
+        self.sponge.squeeze_field_elements(n)
+    }
+
+    /// Squeeze `k` bits, little‑endian, taking the low
+    /// `MODULUS_BIT_SIZE - 1` bits of each squeezed element.
+    pub fn squeeze_bits(&mut self, k: usize) -> Vec<bool> {
+        self.sponge.squeeze_bits(k)
+    }
+
+    /// Squeeze `k` bytes, little‑endian, taking the low
+    /// `(MODULUS_BIT_SIZE - 1) / 8` bytes of each squeezed element.
+    pub fn squeeze_bytes(&mut self, k: usize) -> Vec<u8> {
+        self.sponge.squeeze_bytes(k)
+    }
+
+    /// Blinded commitment to the current sponge state: the hash of
+    /// `blinding` followed by every state lane, under the same parameters.
+    ///
+    /// This is the public input expected by
+    /// [`PoseidonHashVar::from_poseidon_hash_committed`].
+    pub fn state_commitment(&self, blinding: F) -> F {
+        let mut hash = Self::with_config(&self.sponge.parameters);
+        hash.absorb_many([blinding]);
+        hash.absorb_many(self.sponge.state.iter());
+        hash.squeeze()
+    }
+}
+
+impl<F: Absorb + PrimeField> Default for PoseidonHash<F> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Constraint‑system variant of `PoseidonHash`.
+#[derive(Clone)]
 pub struct PoseidonHashVar<F: Absorb + PrimeField> {
     sponge: PoseidonSpongeVar<F>,
 }
@@ -89,46 +258,199 @@ pub struct PoseidonHashVar<F: Absorb + PrimeField> {
 impl<F: Absorb + PrimeField> PoseidonHashVar<F> {
     /// Create a fresh sponge gadget inside the given constraint system.
     pub fn new(cs: ConstraintSystemRef<F>) -> Self {
+        Self::with_config(cs, &cached_poseidon_config::<F>())
+    }
+
+    /// Create a fresh sponge gadget over an explicit configuration.
+    pub fn with_config(cs: ConstraintSystemRef<F>, config: &PoseidonConfig<F>) -> Self {
         Self {
-            sponge: PoseidonSpongeVar::new(cs, &get_poseidon_config::<F>()),
+            sponge: PoseidonSpongeVar::new(cs, config),
         }
     }
 
+    /// Create a sponge gadget whose capacity element is set to `domain`,
+    /// matching [`PoseidonHash::with_domain`]. The tag is a circuit constant.
+    pub fn with_domain(cs: ConstraintSystemRef<F>, domain: F) -> Self {
+        Self::with_config_and_domain(cs, &cached_poseidon_config::<F>(), domain)
+    }
+
+    /// Create a domain‑separated sponge gadget over an explicit configuration.
+    pub fn with_config_and_domain(
+        cs: ConstraintSystemRef<F>,
+        config: &PoseidonConfig<F>,
+        domain: F,
+    ) -> Self {
+        let mut hash = Self::with_config(cs, config);
+        hash.sponge.state[0] = FpVar::Constant(domain);
+        hash
+    }
+
     /// Convert a native sponge into its constraint‑system counterpart.
     /// Useful when part of the computation runs off‑circuit.
+    ///
+    /// Panicking wrapper around [`Self::try_from_poseidon_hash`].
     pub fn from_poseidon_hash(cs: ConstraintSystemRef<F>, native: PoseidonHash<F>) -> Self {
-        let state = native
-            .sponge
-            .state
-            .iter()
-            .map(|&f| FpVar::new_input(cs.clone(), || Ok(f)).unwrap())
-            .collect();
+        Self::try_from_poseidon_hash(cs, native).expect("Error while allocating sponge state")
+    }
 
-        Self {
+    /// Fallible counterpart of [`Self::from_poseidon_hash`]: the native
+    /// state is allocated as public inputs and any allocation failure is
+    /// returned to the caller.
+    pub fn try_from_poseidon_hash(
+        cs: ConstraintSystemRef<F>,
+        native: PoseidonHash<F>,
+    ) -> Result<Self, SynthesisError> {
+        Self::from_poseidon_hash_with_mode(cs, native, AllocationMode::Input)
+    }
+
+    /// Convert a native sponge, allocating its state with `mode`.
+    ///
+    /// `AllocationMode::Witness` keeps the state private, so an
+    /// off‑circuit prefix can be continued in‑circuit without disclosing
+    /// it; `AllocationMode::Constant` bakes the state into the circuit.
+    /// The duplex mode (absorb/squeeze position) is always part of the
+    /// circuit shape.
+    pub fn from_poseidon_hash_with_mode(
+        cs: ConstraintSystemRef<F>,
+        native: PoseidonHash<F>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let state = Vec::<FpVar<F>>::new_variable(cs.clone(), || Ok(native.sponge.state), mode)?;
+
+        Ok(Self {
             sponge: PoseidonSpongeVar {
                 cs,
-                parameters: native.sponge.parameters.clone(),
+                parameters: native.sponge.parameters,
                 state,
-                mode: native.sponge.mode.clone(),
+                mode: native.sponge.mode,
             },
-        }
+        })
+    }
+
+    /// Convert a native sponge whose state is allocated as witnesses and
+    /// bound to a single public input, `native.state_commitment(blinding)`.
+    ///
+    /// Returns the sponge gadget together with the commitment variable.
+    pub fn from_poseidon_hash_committed(
+        cs: ConstraintSystemRef<F>,
+        native: PoseidonHash<F>,
+        blinding: F,
+    ) -> Result<(Self, FpVar<F>), SynthesisError> {
+        let commitment = FpVar::new_input(cs.clone(), || Ok(native.state_commitment(blinding)))?;
+        let blinding = FpVar::new_witness(cs.clone(), || Ok(blinding))?;
+        let hash = Self::from_poseidon_hash_with_mode(cs.clone(), native, AllocationMode::Witness)?;
+
+        let mut opening = PoseidonSpongeVar::new(cs, &hash.sponge.parameters);
+        opening.absorb(&blinding)?;
+        opening.absorb(&hash.sponge.state)?;
+        opening
+            .squeeze_field_elements(1)?
+            .remove(0)
+            .enforce_equal(&commitment)?;
+
+        Ok((hash, commitment))
     }
 
     /// Absorb field gadgets.
-    pub fn absorb_many<I, A>(&mut self, iter: I) -> ()
+    ///
+    /// Panicking wrapper around [`Self::try_absorb_many`].
+    pub fn absorb_many<I, A>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: AbsorbGadget<F>,
+    {
+        self.try_absorb_many(iter)
+            .expect("Error while sponge absorbing");
+    }
+
+    /// Absorb field gadgets, returning the first synthesis error.
+    pub fn try_absorb_many<I, A>(&mut self, iter: I) -> Result<(), SynthesisError>
     where
         I: IntoIterator<Item = A>,
         A: AbsorbGadget<F>,
     {
         for elem in iter {
-            self.sponge.absorb(&elem).expect("Error while sponge absorbing");
+            self.sponge.absorb(&elem)?;
         }
+        Ok(())
+    }
+
+    /// Absorb byte gadgets, packed with [`pack_bytes_var`].
+    ///
+    /// Panicking wrapper around [`Self::try_absorb_bytes`].
+    pub fn absorb_bytes(&mut self, bytes: &[UInt8<F>]) {
+        self.try_absorb_bytes(bytes)
+            .expect("Error while sponge absorbing");
+    }
+
+    /// Absorb byte gadgets, returning the first synthesis error.
+    pub fn try_absorb_bytes(&mut self, bytes: &[UInt8<F>]) -> Result<(), SynthesisError> {
+        self.try_absorb_many(pack_bytes_var(bytes)?.iter())
+    }
+
+    /// Absorb `s` as constant bytes (e.g. a fixed label); hashes like
+    /// [`PoseidonHash::absorb_str`] without adding constraints.
+    ///
+    /// Panicking wrapper around [`Self::try_absorb_str`].
+    pub fn absorb_str(&mut self, s: &str) {
+        self.try_absorb_str(s).expect("Error while sponge absorbing");
+    }
+
+    /// Absorb `s` as constant bytes, returning the first synthesis error.
+    pub fn try_absorb_str(&mut self, s: &str) -> Result<(), SynthesisError> {
+        self.try_absorb_bytes(&UInt8::constant_vec(s.as_bytes()))
     }
 
     /// Squeeze `one` element.
+    ///
+    /// Panicking wrapper around [`Self::try_squeeze`].
     pub fn squeeze(&mut self) -> FpVar<F> {
-        let squeezed_field_element: Vec<FpVar<F>> = self.sponge.squeeze_field_elements(1).unwrap();
-        squeezed_field_element[0].clone()
+        self.try_squeeze().expect("Error while sponge squeezing")
+    }
+
+    /// Squeeze `one` element, returning the first synthesis error.
+    pub fn try_squeeze(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        let mut squeezed_field_element: Vec<FpVar<F>> = self.sponge.squeeze_field_elements(1)?;
+        Ok(squeezed_field_element.remove(0))
+    }
+
+    /// Squeeze `n` elements.
+    ///
+    /// Panicking wrapper around [`Self::try_squeeze_n`].
+    pub fn squeeze_n(&mut self, n: usize) -> Vec<FpVar<F>> {
+        self.try_squeeze_n(n).expect("Error while sponge squeezing")
+    }
+
+    /// Squeeze `n` elements, returning the first synthesis error.
+    pub fn try_squeeze_n(&mut self, n: usize) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.sponge.squeeze_field_elements(n)
+    }
+
+    /// Squeeze `k` bits, matching [`PoseidonHash::squeeze_bits`].
+    ///
+    /// Panicking wrapper around [`Self::try_squeeze_bits`].
+    pub fn squeeze_bits(&mut self, k: usize) -> Vec<Boolean<F>> {
+        self.try_squeeze_bits(k)
+            .expect("Error while sponge squeezing")
+    }
+
+    /// Squeeze `k` bits through the strict bit decomposition of each
+    /// element, returning the first synthesis error.
+    pub fn try_squeeze_bits(&mut self, k: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        self.sponge.squeeze_bits(k)
+    }
+
+    /// Squeeze `k` bytes, matching [`PoseidonHash::squeeze_bytes`].
+    ///
+    /// Panicking wrapper around [`Self::try_squeeze_bytes`].
+    pub fn squeeze_bytes(&mut self, k: usize) -> Vec<UInt8<F>> {
+        self.try_squeeze_bytes(k)
+            .expect("Error while sponge squeezing")
+    }
+
+    /// Squeeze `k` bytes, returning the first synthesis error.
+    pub fn try_squeeze_bytes(&mut self, k: usize) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        self.sponge.squeeze_bytes(k)
     }
 }
 
@@ -165,4 +487,142 @@ mod tests {
             gadget.squeeze().value().unwrap()
         );
     }
+
+    #[test]
+    fn gadget_errors_are_returned() {
+        let native = PoseidonHash::<Fr>::new();
+        assert!(matches!(
+            PoseidonHashVar::try_from_poseidon_hash(ConstraintSystemRef::None, native),
+            Err(SynthesisError::MissingCS)
+        ));
+    }
+
+    #[test]
+    fn distinct_domains_do_not_collide() {
+        let inputs = [Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)];
+        let tags = [
+            Fr::from(0u64),
+            domain_tag(b"report"),
+            domain_tag(b"nullifier"),
+            domain_tag(b"merkle"),
+            domain_tag(b"merkle\0"),
+        ];
+
+        let cs = ConstraintSystem::new_ref();
+        let vars = inputs.map(FpVar::Constant);
+        let mut digests = Vec::new();
+        for tag in tags {
+            let mut native = PoseidonHash::with_domain(tag);
+            native.absorb_many(inputs.iter());
+            let digest = native.squeeze();
+
+            let mut gadget = PoseidonHashVar::with_domain(cs.clone(), tag);
+            gadget.try_absorb_many(vars.iter()).unwrap();
+            assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), digest);
+            digests.push(digest);
+        }
+
+        let mut untagged = PoseidonHash::<Fr>::new();
+        untagged.absorb_many(inputs.iter());
+        assert_eq!(untagged.squeeze(), digests[0]);
+
+        for (i, a) in digests.iter().enumerate() {
+            assert!(digests[i + 1..].iter().all(|b| a != b));
+        }
+        assert!(tags[1..].iter().all(|tag| *tag != Fr::from(0u64)));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty personalization")]
+    fn empty_domain_is_rejected() {
+        let _: Fr = domain_tag(b"");
+    }
+
+    #[test]
+    fn bytes_hash_identically_off_and_in_circuit() {
+        let report = b"report #17: leak in building C, floor 3 -- severity high; contact withheld";
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        for len in [0, 1, 30, 31, 32, 62, report.len()] {
+            let bytes = &report[..len];
+            let mut native = PoseidonHash::<Fr>::new();
+            native.absorb_bytes(bytes);
+
+            let vars = UInt8::new_witness_vec(cs.clone(), bytes).unwrap();
+            let mut gadget = PoseidonHashVar::new(cs.clone());
+            gadget.try_absorb_bytes(&vars).unwrap();
+            assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), native.squeeze());
+        }
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut native = PoseidonHash::<Fr>::new();
+        native.absorb_str("severity");
+        let mut gadget = PoseidonHashVar::<Fr>::new(ConstraintSystem::new_ref());
+        gadget.try_absorb_str("severity").unwrap();
+        assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), native.squeeze());
+
+        // trailing zeros and chunk boundaries do not collide
+        assert_ne!(pack_bytes::<Fr>(b"ab"), pack_bytes::<Fr>(b"ab\0"));
+        assert_ne!(pack_bytes::<Fr>(&[1; 31]), pack_bytes::<Fr>(&[1; 32])[..2]);
+    }
+
+    #[test]
+    fn multi_element_bit_and_byte_squeezes() {
+        let mut native = PoseidonHash::<Fr>::new();
+        native.absorb_bytes(b"report");
+
+        let cs = ConstraintSystem::new_ref();
+        let report = UInt8::new_witness_vec(cs.clone(), b"report").unwrap();
+        let mut gadget = PoseidonHashVar::<Fr>::new(cs.clone());
+        gadget.try_absorb_bytes(&report).unwrap();
+
+        let challenges = native.squeeze_n(5);
+        assert_eq!(challenges.len(), 5);
+        assert_eq!(gadget.try_squeeze_n(5).unwrap().value().unwrap(), challenges);
+
+        let key = native.squeeze_bytes(32);
+        assert_eq!(key.len(), 32);
+        assert_eq!(gadget.try_squeeze_bytes(32).unwrap().value().unwrap(), key);
+
+        let id = native.squeeze_bits(300);
+        assert_eq!(id.len(), 300);
+        assert_eq!(gadget.try_squeeze_bits(300).unwrap().value().unwrap(), id);
+
+        // the next single squeeze continues from the same position
+        assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), native.squeeze());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn lift_native_prefix_with_allocation_mode() {
+        let mut native = PoseidonHash::<Fr>::new();
+        native.absorb_many([Fr::from(7u64), Fr::from(11u64)]);
+        let expected = native.clone().squeeze();
+
+        for (mode, inputs, witnesses) in [
+            (AllocationMode::Constant, 1, 0),
+            (AllocationMode::Witness, 1, 5),
+            (AllocationMode::Input, 6, 0),
+        ] {
+            let cs = ConstraintSystem::new_ref();
+            let mut gadget =
+                PoseidonHashVar::from_poseidon_hash_with_mode(cs.clone(), native.clone(), mode)
+                    .unwrap();
+            assert_eq!(cs.num_instance_variables(), inputs);
+            assert_eq!(cs.num_witness_variables(), witnesses);
+            assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // only the blinded commitment is public
+        let blinding = Fr::from(42u64);
+        let cs = ConstraintSystem::new_ref();
+        let (mut gadget, commitment) =
+            PoseidonHashVar::from_poseidon_hash_committed(cs.clone(), native.clone(), blinding)
+                .unwrap();
+        assert_eq!(cs.num_instance_variables(), 2);
+        assert_eq!(commitment.value().unwrap(), native.state_commitment(blinding));
+        assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+        assert_ne!(native.state_commitment(blinding), native.state_commitment(Fr::ONE));
+    }
 }
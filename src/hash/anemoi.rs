@@ -0,0 +1,291 @@
+//! Anemoi permutation and sponge wrappers.
+//!
+//! Anemoi (Bouvier et al., <https://eprint.iacr.org/2022/840.pdf>)
+//! splits the state into halves `x` and `y` of `l` words and, in each
+//! of its `N` rounds, adds constants, applies the linear layer (`M` on
+//! `x`, `M` after a one‑word rotation on `y`), a pseudo‑Hadamard
+//! transform and one open Flystel per `(x_j, y_j)` pair:
+//!
+//! ```text
+//! x <- x - g·y²
+//! y <- y - x^{1/alpha}
+//! x <- x + g·y² + g^{-1}
+//! ```
+//!
+//! The Flystel is cheap to verify in R1CS (two squarings plus one
+//! `alpha`‑th power check for two lanes), which gives the lowest
+//! constraint count of the hashes in this crate at the price of a
+//! full‑size exponentiation per pair natively.
+//!
+//! Only `l = 1, 2` (widths 2 and 4) are supported, the widths whose
+//! `M` the paper fixes explicitly. `N` follows the reference
+//! implementation: the smallest round count meeting the paper’s Gröbner
+//! basis bound, plus 2 rounds for its second attack model and
+//! `min(5, l + 1)` rounds of security margin, and at least 8. Round
+//! constants are drawn from the Poseidon Grain LFSR rather than the
+//! digits of π, so digests differ from the reference implementation.
+
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{FieldVar, fp::FpVar};
+use ark_relations::r1cs::SynthesisError;
+
+use super::params::{
+    PoseidonParamsBuilder, PoseidonParamsError, inverse_alpha, log2_binomial, smallest_alpha,
+};
+use super::registry::{self, ParamsKey};
+use super::sponge::{DuplexSponge, DuplexSpongeVar, SpongePermutation, inverse_sbox_var};
+
+/// Anemoi configuration (capacity 1, width `2l`).
+#[derive(Clone, Debug)]
+pub struct AnemoiConfig<F: PrimeField> {
+    /// Number of rounds `N`.
+    pub rounds: usize,
+    /// S‑box exponent.
+    pub alpha: u64,
+    /// Little‑endian limbs of `alpha^{-1} mod (p - 1)`.
+    pub alpha_inv: Vec<u64>,
+    /// Multiplicative generator `g` used by `M` and the Flystel.
+    pub generator: F,
+    /// `g^{-1}`, the Flystel’s additive constant.
+    pub generator_inv: F,
+    /// Round constants for `x` (`C`) and `y` (`D`), indexed `[round][lane]`.
+    pub round_constants: Vec<Vec<F>>,
+    /// The rate (in field elements).
+    pub rate: usize,
+    /// The capacity (in field elements).
+    pub capacity: usize,
+}
+
+impl PoseidonParamsBuilder {
+    /// Returns the number of Anemoi rounds for the field `F`.
+    pub fn anemoi_rounds<F: PrimeField>(&self) -> Result<usize, PoseidonParamsError> {
+        self.validate::<F>()?;
+        let l = self.width() / 2;
+        let alpha = self.sbox_exponent();
+        // the paper's κ_α; other exponents have no published bound
+        let kappa = match alpha {
+            3 => 1.0,
+            5 => 2.0,
+            7 => 4.0,
+            9 => 7.0,
+            11 => 9.0,
+            _ => return Err(PoseidonParamsError::InvalidAlpha(alpha)),
+        };
+        let security = self.target_security_level() as f64;
+
+        let rounds = (1..100)
+            .find(|&n| {
+                let ln = (l * n) as f64;
+                2.0 * log2_binomial(4.0 * ln + kappa, 2.0 * ln) >= security
+            })
+            .ok_or(PoseidonParamsError::NoRoundNumbers)?;
+        // +2 for the second Gröbner model, +min(5, l + 1) security margin
+        Ok((rounds + 2 + (l + 1).min(5)).max(8))
+    }
+
+    /// Build (or fetch from the registry) an Anemoi configuration for `F`.
+    ///
+    /// Only arities 1 and 3 (widths 2 and 4) are accepted.
+    pub fn build_anemoi<F: PrimeField>(&self) -> Result<Arc<AnemoiConfig<F>>, PoseidonParamsError> {
+        registry::get_or_try_init(*self, || {
+            let width = self.width();
+            if width != 2 && width != 4 {
+                return Err(PoseidonParamsError::UnsupportedWidth(width));
+            }
+            let rounds = self.anemoi_rounds::<F>()?;
+            let alpha = self.sbox_exponent();
+            let key = ParamsKey {
+                width,
+                full_rounds: rounds,
+                partial_rounds: 0,
+                alpha,
+            };
+
+            Ok(registry::get_or_init(key, || {
+                let (round_constants, _) = find_poseidon_ark_and_mds::<F>(
+                    F::MODULUS_BIT_SIZE as u64,
+                    width - 1,
+                    rounds as u64,
+                    0,
+                    0, // seed
+                );
+                AnemoiConfig {
+                    rounds,
+                    alpha,
+                    alpha_inv: inverse_alpha::<F>(alpha),
+                    generator: F::GENERATOR,
+                    generator_inv: F::GENERATOR.inverse().unwrap(),
+                    round_constants,
+                    rate: width - 1,
+                    capacity: 1,
+                }
+            }))
+        })
+    }
+}
+
+/// Default Anemoi configuration: `l = 2` (arity 3), 128‑bit security and
+/// the smallest S‑box exponent that permutes `F`.
+pub fn cached_anemoi_config<F: PrimeField>() -> Arc<AnemoiConfig<F>> {
+    PoseidonParamsBuilder::new()
+        .arity(3)
+        .alpha(smallest_alpha::<F>())
+        .build_anemoi::<F>()
+        .expect("default Anemoi parameters are valid for every prime field")
+}
+
+impl<F: PrimeField> SpongePermutation<F> for AnemoiConfig<F> {
+    fn cached_default() -> Arc<Self> {
+        cached_anemoi_config::<F>()
+    }
+
+    fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        let l = state.len() / 2;
+        for constants in &self.round_constants {
+            state.iter_mut().zip(constants).for_each(|(s, c)| *s += c);
+            self.linear_layer(state);
+            for j in 0..l {
+                let (mut x, mut y) = (state[j], state[l + j]);
+                x -= self.generator * y.square();
+                y -= x.pow(&self.alpha_inv);
+                x += self.generator * y.square() + self.generator_inv;
+                (state[j], state[l + j]) = (x, y);
+            }
+        }
+        self.linear_layer(state);
+    }
+
+    fn permute_var(&self, state: &mut [FpVar<F>]) -> Result<(), SynthesisError> {
+        let l = state.len() / 2;
+        for constants in &self.round_constants {
+            state.iter_mut().zip(constants).for_each(|(s, c)| *s += *c);
+            self.linear_layer_var(state);
+            for j in 0..l {
+                let x = &state[j] - state[l + j].square()? * self.generator;
+                let y = &state[l + j] - inverse_sbox_var(&x, self.alpha, &self.alpha_inv)?;
+                state[j] = x + y.square()? * self.generator + self.generator_inv;
+                state[l + j] = y;
+            }
+        }
+        self.linear_layer_var(state);
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> AnemoiConfig<F> {
+    /// `x <- M x`, `y <- M rot(y)`, then the pseudo‑Hadamard transform.
+    fn linear_layer(&self, state: &mut [F]) {
+        let g = self.generator;
+        if state.len() == 4 {
+            let [x0, x1, y0, y1] = [state[0], state[1], state[2], state[3]];
+            state[0] = x0 + g * x1;
+            state[1] = g * x0 + (g.square() + F::ONE) * x1;
+            state[2] = y1 + g * y0;
+            state[3] = g * y1 + (g.square() + F::ONE) * y0;
+        }
+        let l = state.len() / 2;
+        for j in 0..l {
+            state[l + j] += state[j];
+            state[j] += state[l + j];
+        }
+    }
+
+    fn linear_layer_var(&self, state: &mut [FpVar<F>]) {
+        let g = self.generator;
+        if state.len() == 4 {
+            let [x0, x1, y0, y1] = [&state[0], &state[1], &state[2], &state[3]];
+            let new_state = [
+                x0 + x1 * g,
+                x0 * g + x1 * (g.square() + F::ONE),
+                y1 + y0 * g,
+                y1 * g + y0 * (g.square() + F::ONE),
+            ];
+            state.clone_from_slice(&new_state);
+        }
+        let l = state.len() / 2;
+        for j in 0..l {
+            state[l + j] = &state[l + j] + &state[j];
+            state[j] = &state[j] + &state[l + j];
+        }
+    }
+}
+
+/// Native‑field Anemoi hash helper.
+pub type AnemoiHash<F> = DuplexSponge<F, AnemoiConfig<F>>;
+
+/// Constraint‑system variant of `AnemoiHash`.
+pub type AnemoiHashVar<F> = DuplexSpongeVar<F, AnemoiConfig<F>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{R1CSVar, alloc::AllocVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn anemoi_native_vs_r1cs() {
+        for arity in [1, 3] {
+            let config = PoseidonParamsBuilder::new()
+                .arity(arity)
+                .build_anemoi::<Fr>()
+                .unwrap();
+            let inputs: Vec<Fr> = (1u64..=5).map(Fr::from).collect();
+            let mut native = AnemoiHash::with_config(config.clone());
+            native.absorb_many(inputs.iter());
+
+            let cs = ConstraintSystem::new_ref();
+            let vars: Vec<FpVar<Fr>> = inputs
+                .iter()
+                .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+                .collect();
+            let mut gadget = AnemoiHashVar::with_config(cs.clone(), config);
+            gadget.absorb_many(vars.iter());
+
+            for _ in 0..3 {
+                assert_eq!(gadget.squeeze().value().unwrap(), native.squeeze());
+            }
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn round_numbers_match_the_paper() {
+        // 128‑bit security, alpha = 5 on both curves: 21 rounds for l = 1,
+        // 14 for l = 2
+        fn rounds<F: PrimeField>(arity: usize) -> usize {
+            PoseidonParamsBuilder::new()
+                .arity(arity)
+                .alpha(smallest_alpha::<F>())
+                .anemoi_rounds::<F>()
+                .unwrap()
+        }
+        assert_eq!((rounds::<Fr>(1), rounds::<Fr>(3)), (21, 14));
+        type Bls = ark_bls12_381::Fr;
+        assert_eq!((rounds::<Bls>(1), rounds::<Bls>(3)), (21, 14));
+
+        let config = cached_anemoi_config::<Fr>();
+        assert_eq!(config.rounds, 14);
+        assert!(Arc::ptr_eq(&config, &cached_anemoi_config::<Fr>()));
+    }
+
+    #[test]
+    fn rejects_odd_widths() {
+        let err = PoseidonParamsBuilder::new()
+            .arity(2)
+            .build_anemoi::<Fr>()
+            .unwrap_err();
+        assert_eq!(err, PoseidonParamsError::UnsupportedWidth(3));
+    }
+}
@@ -0,0 +1,193 @@
+//! Serializable [`PoseidonHash`] checkpoints.
+//!
+//! A checkpoint stores the sponge state and duplex mode together with a
+//! format version and a fingerprint of the Poseidon parameters, so a
+//! partial hash of a large report can be persisted, resumed in another
+//! process, or lifted into a circuit with
+//! [`PoseidonHashVar::from_poseidon_hash`](super::PoseidonHashVar::from_poseidon_hash).
+//!
+//! Layout: `version: u8 ‖ fingerprint: [u8; 32] ‖ state: Vec<F> ‖
+//! mode: u8 (0 absorbing, 1 squeezing) ‖ index: u64`. The parameters
+//! themselves are not stored: [`CanonicalDeserialize`] resumes over the
+//! canonical configuration and [`PoseidonHash::deserialize_with_config`]
+//! over an explicit one, both rejecting a fingerprint mismatch.
+
+use std::io::{Read, Write};
+
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, DuplexSpongeMode};
+use ark_ff::PrimeField;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+
+use super::{PoseidonHash, cached_poseidon_config};
+use crate::digest::sha3_256;
+
+/// Current checkpoint format version.
+pub const CHECKPOINT_VERSION: u8 = 1;
+
+/// SHA3‑256 of the compressed serialization of `config`.
+pub fn config_fingerprint<F: PrimeField>(config: &PoseidonConfig<F>) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    config
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    sha3_256(&bytes)
+}
+
+fn mode_parts(mode: &DuplexSpongeMode) -> (u8, usize) {
+    match *mode {
+        DuplexSpongeMode::Absorbing { next_absorb_index } => (0, next_absorb_index),
+        DuplexSpongeMode::Squeezing { next_squeeze_index } => (1, next_squeeze_index),
+    }
+}
+
+impl<F: Absorb + PrimeField> PoseidonHash<F> {
+    /// Resume a checkpoint written over the explicit configuration `config`.
+    pub fn deserialize_with_config<R: Read>(
+        mut reader: R,
+        config: &PoseidonConfig<F>,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let version = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+        let fingerprint = <[u8; 32]>::deserialize_with_mode(&mut reader, compress, validate)?;
+        if version != CHECKPOINT_VERSION || fingerprint != config_fingerprint(config) {
+            return Err(SerializationError::InvalidData);
+        }
+
+        let state = Vec::<F>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let tag = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+        let index = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let mode = match tag {
+            0 => DuplexSpongeMode::Absorbing {
+                next_absorb_index: index,
+            },
+            1 => DuplexSpongeMode::Squeezing {
+                next_squeeze_index: index,
+            },
+            _ => return Err(SerializationError::InvalidData),
+        };
+
+        let mut sponge = PoseidonSponge::new(config);
+        sponge.state = state;
+        sponge.mode = mode;
+        let hash = Self { sponge };
+        if validate == Validate::Yes {
+            hash.check()?;
+        }
+        Ok(hash)
+    }
+}
+
+impl<F: Absorb + PrimeField> CanonicalSerialize for PoseidonHash<F> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        let (tag, index) = mode_parts(&self.sponge.mode);
+        CHECKPOINT_VERSION.serialize_with_mode(&mut writer, compress)?;
+        config_fingerprint(&self.sponge.parameters).serialize_with_mode(&mut writer, compress)?;
+        self.sponge
+            .state
+            .serialize_with_mode(&mut writer, compress)?;
+        tag.serialize_with_mode(&mut writer, compress)?;
+        index.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        let (tag, index) = mode_parts(&self.sponge.mode);
+        CHECKPOINT_VERSION.serialized_size(compress)
+            + [0u8; 32].serialized_size(compress)
+            + self.sponge.state.serialized_size(compress)
+            + tag.serialized_size(compress)
+            + index.serialized_size(compress)
+    }
+}
+
+impl<F: Absorb + PrimeField> Valid for PoseidonHash<F> {
+    fn check(&self) -> Result<(), SerializationError> {
+        let params = &self.sponge.parameters;
+        let (_, index) = mode_parts(&self.sponge.mode);
+        if self.sponge.state.len() != params.rate + params.capacity || index > params.rate {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl<F: Absorb + PrimeField> CanonicalDeserialize for PoseidonHash<F> {
+    /// Resumes over [`cached_poseidon_config`]; use
+    /// [`PoseidonHash::deserialize_with_config`] for other parameters.
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_config(reader, &cached_poseidon_config::<F>(), compress, validate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{PoseidonHashVar, PoseidonParamsBuilder};
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{R1CSVar, alloc::AllocationMode, fields::fp::FpVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn checkpoint_resumes_natively_and_in_circuit() {
+        let report: Vec<Fr> = (0u64..11).map(Fr::from).collect();
+        let mut uninterrupted = PoseidonHash::<Fr>::new();
+        uninterrupted.absorb_many(report.iter());
+        let expected = uninterrupted.squeeze();
+
+        let mut partial = PoseidonHash::<Fr>::new();
+        partial.absorb_many(report[..6].iter());
+        let mut bytes = Vec::new();
+        partial.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), partial.compressed_size());
+
+        let mut resumed = PoseidonHash::<Fr>::deserialize_compressed(&bytes[..]).unwrap();
+        let lifted = resumed.clone();
+        resumed.absorb_many(report[6..].iter());
+        assert_eq!(resumed.squeeze(), expected);
+
+        let cs = ConstraintSystem::new_ref();
+        let mut gadget = PoseidonHashVar::from_poseidon_hash_with_mode(
+            cs.clone(),
+            lifted,
+            AllocationMode::Witness,
+        )
+        .unwrap();
+        gadget
+            .try_absorb_many(report[6..].iter().map(|&x| FpVar::Constant(x)))
+            .unwrap();
+        assert_eq!(gadget.try_squeeze().unwrap().value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn rejects_foreign_parameters_and_versions() {
+        let config = PoseidonParamsBuilder::new().arity(2).build::<Fr>().unwrap();
+        let mut hash = PoseidonHash::with_config(&config);
+        hash.absorb_many([Fr::from(1u64)]);
+        let mut bytes = Vec::new();
+        hash.serialize_uncompressed(&mut bytes).unwrap();
+
+        assert!(PoseidonHash::<Fr>::deserialize_uncompressed(&bytes[..]).is_err());
+        let resumed =
+            PoseidonHash::deserialize_with_config(&bytes[..], &config, Compress::No, Validate::Yes)
+                .unwrap();
+        assert_eq!(resumed.sponge.state, hash.sponge.state);
+
+        bytes[0] = CHECKPOINT_VERSION + 1;
+        assert!(
+            PoseidonHash::deserialize_with_config(&bytes[..], &config, Compress::No, Validate::Yes)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,397 @@
+//! Poseidon parameter selection for arbitrary widths.
+//!
+//! [`PoseidonParamsBuilder`] derives the number of full and partial
+//! rounds from the state width, the target security level and the
+//! S‑box exponent, following the round‑number script that ships
+//! with the Poseidon paper (Grassi et al.,
+//! <https://eprint.iacr.org/2019/458.pdf>, including the Gröbner
+//! basis bound added in <https://eprint.iacr.org/2023/537.pdf>).
+//! Round constants and MDS come from the same Grain LFSR as
+//! [`get_poseidon_config`](super::get_poseidon_config), and built
+//! configurations are shared through the [`registry`](super::registry).
+//!
+//! With the defaults (128‑bit security, x^5) the builder reproduces
+//! circomlib’s round numbers for every `t = 2..17` on BN254.
+
+use std::fmt;
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, find_poseidon_ark_and_mds};
+use ark_ff::PrimeField;
+
+use super::registry::{self, ParamsKey};
+
+/// Smallest supported state width.
+pub const MIN_WIDTH: usize = 2;
+/// Largest supported state width.
+pub const MAX_WIDTH: usize = 17;
+
+/// Errors returned by [`PoseidonParamsBuilder::build`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoseidonParamsError {
+    /// The requested state width is outside `MIN_WIDTH..=MAX_WIDTH`.
+    UnsupportedWidth(usize),
+    /// `x^alpha` is not a permutation of the field (or `alpha < 3`).
+    InvalidAlpha(u64),
+    /// The security level is zero.
+    InvalidSecurityLevel(u32),
+    /// No round numbers within the search bounds meet the security level.
+    NoRoundNumbers,
+}
+
+impl fmt::Display for PoseidonParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedWidth(t) => {
+                write!(f, "state width {t} outside {MIN_WIDTH}..={MAX_WIDTH}")
+            }
+            Self::InvalidAlpha(alpha) => {
+                write!(f, "x^{alpha} is not a permutation of the field")
+            }
+            Self::InvalidSecurityLevel(bits) => write!(f, "invalid security level {bits}"),
+            Self::NoRoundNumbers => write!(f, "no round numbers reach the security level"),
+        }
+    }
+}
+
+impl std::error::Error for PoseidonParamsError {}
+
+/// Builder for Poseidon configurations with capacity 1.
+///
+/// Defaults to arity 4 (`t = 5`), 128‑bit security and `alpha = 5`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoseidonParamsBuilder {
+    width: usize,
+    security_level: u32,
+    alpha: u64,
+}
+
+impl Default for PoseidonParamsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseidonParamsBuilder {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self {
+            width: 5,
+            security_level: 128,
+            alpha: 5,
+        }
+    }
+
+    /// Number of field elements absorbed per permutation (`t = arity + 1`).
+    pub fn arity(mut self, arity: usize) -> Self {
+        self.width = arity + 1;
+        self
+    }
+
+    /// Target security level in bits.
+    pub fn security_level(mut self, bits: u32) -> Self {
+        self.security_level = bits;
+        self
+    }
+
+    /// S‑box exponent; must be coprime with `p - 1`.
+    pub fn alpha(mut self, alpha: u64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Returns `(full_rounds, partial_rounds)` for the field `F`.
+    ///
+    /// The reference security margin is applied (two extra full rounds,
+    /// 7.5% extra partial rounds), and the partial rounds are then rounded
+    /// up to a multiple of `t` as circomlib does.
+    pub fn round_numbers<F: PrimeField>(&self) -> Result<(usize, usize), PoseidonParamsError> {
+        let (full, partial) = self.secure_round_numbers::<F>()?;
+        Ok((full, partial.div_ceil(self.width) * self.width))
+    }
+
+    /// Cheapest `(full_rounds, partial_rounds)` meeting the security level,
+    /// including the reference security margin.
+    pub(crate) fn secure_round_numbers<F: PrimeField>(
+        &self,
+    ) -> Result<(usize, usize), PoseidonParamsError> {
+        self.validate::<F>()?;
+
+        let t = self.width;
+        let log_p = log2_modulus::<F>();
+        let mut best: Option<(usize, usize)> = None;
+
+        for partial in 1..500 {
+            // the cost (number of S‑boxes) grows with R_F, so the first hit is the best one
+            let Some(full) = (4..100)
+                .step_by(2)
+                .find(|&full| self.is_secure(log_p, full, partial))
+            else {
+                continue;
+            };
+
+            let full = full + 2;
+            let partial = (partial as f64 * 1.075).ceil() as usize;
+            let cost = |(rf, rp): (usize, usize)| rf * t + rp;
+            let better = best.is_none_or(|b| {
+                let (new, old) = (cost((full, partial)), cost(b));
+                new < old || (new == old && full < b.0)
+            });
+            if better {
+                best = Some((full, partial));
+            }
+        }
+
+        best.ok_or(PoseidonParamsError::NoRoundNumbers)
+    }
+
+    /// Build (or fetch from the registry) the configuration for `F`.
+    ///
+    /// Only the first build for a given set of inputs searches for round
+    /// numbers; later ones are a registry lookup.
+    pub fn build<F: PrimeField>(&self) -> Result<Arc<PoseidonConfig<F>>, PoseidonParamsError> {
+        registry::get_or_try_init(*self, || {
+            let (full_rounds, partial_rounds) = self.round_numbers::<F>()?;
+            let key = ParamsKey {
+                width: self.width,
+                full_rounds,
+                partial_rounds,
+                alpha: self.alpha,
+            };
+
+            Ok(registry::get_or_init(key, || {
+                let rate = self.width - 1;
+                let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+                    F::MODULUS_BIT_SIZE as u64,
+                    rate,
+                    full_rounds as u64,
+                    partial_rounds as u64,
+                    0, // seed
+                );
+                PoseidonConfig::new(full_rounds, partial_rounds, self.alpha, mds, ark, rate, 1)
+            }))
+        })
+    }
+
+    /// State width `t`.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Configured S‑box exponent.
+    pub fn sbox_exponent(&self) -> u64 {
+        self.alpha
+    }
+
+    /// Configured security level in bits.
+    pub fn target_security_level(&self) -> u32 {
+        self.security_level
+    }
+
+    pub(crate) fn validate<F: PrimeField>(&self) -> Result<(), PoseidonParamsError> {
+        if !(MIN_WIDTH..=MAX_WIDTH).contains(&self.width) {
+            return Err(PoseidonParamsError::UnsupportedWidth(self.width));
+        }
+        if self.security_level == 0 {
+            return Err(PoseidonParamsError::InvalidSecurityLevel(self.security_level));
+        }
+        if !is_permutation::<F>(self.alpha) {
+            return Err(PoseidonParamsError::InvalidAlpha(self.alpha));
+        }
+        Ok(())
+    }
+
+    /// Security inequalities of the reference `sat_inequiv_alpha`.
+    fn is_secure(&self, log_p: f64, full: usize, partial: usize) -> bool {
+        let t = self.width as f64;
+        let m = self.security_level as f64;
+        let n = log_p.ceil();
+        let (rf, rp) = (full as f64, partial as f64);
+        let log_alpha_2 = 1.0 / (self.alpha as f64).log2(); // log_alpha(2)
+
+        // statistical
+        let rf_1 = if m <= (log_p - (self.alpha - 1) as f64 / 2.0).floor() * (t + 1.0) {
+            6.0
+        } else {
+            10.0
+        };
+        // interpolation
+        let rf_2 = 1.0 + (log_alpha_2 * m.min(n)).ceil() + (t.log2() * log_alpha_2).ceil() - rp;
+        // Gröbner basis attacks
+        let rf_3 = log_alpha_2 * m.min(log_p) - rp;
+        let rf_4 = t - 1.0 + log_alpha_2 * (m / (t + 1.0)).min(log_p / 2.0) - rp;
+        let rf_5 = (t - 2.0 + m / (2.0 * (self.alpha as f64).log2()) - rp) / (t - 1.0);
+
+        let rf_max = [rf_1, rf_2, rf_3, rf_4, rf_5]
+            .into_iter()
+            .map(f64::ceil)
+            .fold(f64::MIN, f64::max);
+        if rf < rf_max {
+            return false;
+        }
+
+        // https://eprint.iacr.org/2023/537.pdf
+        let r = (t / 3.0).floor();
+        let alpha = self.alpha as f64;
+        let over = (rf - 1.0) * t + rp + r + r * (rf / 2.0) + rp + alpha;
+        let under = r * (rf / 2.0) + rp + alpha;
+        (2.0 * log2_binomial(over, under)).ceil() >= m
+    }
+}
+
+/// Smallest S‑box exponent `alpha >= 3` for which `x^alpha` permutes `F`.
+pub fn smallest_alpha<F: PrimeField>() -> u64 {
+    (3..)
+        .step_by(2)
+        .find(|&alpha| is_permutation::<F>(alpha))
+        .unwrap()
+}
+
+/// Little‑endian limbs of `alpha^{-1} mod (p - 1)`, i.e. the exponent of
+/// the inverse S‑box `x^{1/alpha}`.
+pub(crate) fn inverse_alpha<F: PrimeField>(alpha: u64) -> Vec<u64> {
+    // find j with alpha | 1 + j (p - 1); the inverse is (1 + j (p - 1)) / alpha
+    let r = modulus_minus_one_mod::<F>(alpha);
+    let j = (1..alpha)
+        .find(|j| (1 + j * r).is_multiple_of(alpha))
+        .expect("alpha is coprime with p - 1");
+
+    let mut limbs = F::MODULUS.as_ref().to_vec();
+    limbs[0] -= 1; // p is odd
+    let mut carry = 1u128;
+    for limb in limbs.iter_mut() {
+        let v = *limb as u128 * j as u128 + carry;
+        *limb = v as u64;
+        carry = v >> 64;
+    }
+    limbs.push(carry as u64);
+
+    let mut rem = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let v = (rem << 64) | *limb as u128;
+        *limb = (v / alpha as u128) as u64;
+        rem = v % alpha as u128;
+    }
+    debug_assert_eq!(rem, 0);
+    limbs
+}
+
+fn is_permutation<F: PrimeField>(alpha: u64) -> bool {
+    alpha >= 3 && gcd(alpha, modulus_minus_one_mod::<F>(alpha)) == 1
+}
+
+/// `log2(p)` as a float.
+fn log2_modulus<F: PrimeField>() -> f64 {
+    F::MODULUS
+        .as_ref()
+        .iter()
+        .rev()
+        .fold(0.0, |acc, &limb| acc * 2f64.powi(64) + limb as f64)
+        .log2()
+}
+
+/// `(p - 1) mod m`.
+fn modulus_minus_one_mod<F: PrimeField>(m: u64) -> u64 {
+    let p_mod = F::MODULUS
+        .as_ref()
+        .iter()
+        .rev()
+        .fold(0u128, |acc, &limb| ((acc << 64) | limb as u128) % m as u128) as u64;
+    (p_mod + m - 1) % m
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// `log2(n choose k)` for non‑negative integral `n >= k`.
+pub(crate) fn log2_binomial(n: f64, k: f64) -> f64 {
+    let k = k.min(n - k) as u64;
+    (1..=k)
+        .map(|i| ((n - k as f64 + i as f64) / i as f64).log2())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{PoseidonHash, PoseidonHashVar, get_poseidon_config};
+    use ark_r1cs_std::{R1CSVar, fields::fp::FpVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn reproduces_circomlib_round_numbers() {
+        // circomlib's N_ROUNDS_P for t = 2..17
+        let circomlib = [
+            56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+        ];
+        for (arity, partial) in (1..).zip(circomlib) {
+            let rounds = PoseidonParamsBuilder::new()
+                .arity(arity)
+                .round_numbers::<ark_bn254::Fr>()
+                .unwrap();
+            assert_eq!(rounds, (8, partial), "t = {}", arity + 1);
+        }
+
+        let default = PoseidonParamsBuilder::new().build::<ark_bn254::Fr>().unwrap();
+        let legacy = get_poseidon_config::<ark_bn254::Fr>();
+        assert_eq!(default.ark, legacy.ark);
+        assert_eq!(default.mds, legacy.mds);
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let builder = PoseidonParamsBuilder::new();
+        assert_eq!(
+            builder.arity(0).build::<ark_bn254::Fr>().unwrap_err(),
+            PoseidonParamsError::UnsupportedWidth(1)
+        );
+        assert_eq!(
+            builder.arity(17).build::<ark_bn254::Fr>().unwrap_err(),
+            PoseidonParamsError::UnsupportedWidth(18)
+        );
+        // 3 | p - 1 on BN254, 5 | p - 1 on BLS12‑377
+        assert_eq!(
+            builder.alpha(3).build::<ark_bn254::Fr>().unwrap_err(),
+            PoseidonParamsError::InvalidAlpha(3)
+        );
+        assert_eq!(
+            builder.build::<ark_bls12_377::Fr>().unwrap_err(),
+            PoseidonParamsError::InvalidAlpha(5)
+        );
+        assert!(builder.alpha(17).build::<ark_bls12_377::Fr>().is_ok());
+    }
+
+    #[test]
+    fn arity_two_native_vs_r1cs() {
+        use ark_bn254::Fr;
+        let config = PoseidonParamsBuilder::new().arity(2).build::<Fr>().unwrap();
+        assert_eq!(config.rate, 2);
+
+        let mut native = PoseidonHash::with_config(&config);
+        native.absorb_many([Fr::from(1u64), Fr::from(2u64)]);
+
+        let cs = ConstraintSystem::new_ref();
+        let mut gadget = PoseidonHashVar::with_config(cs.clone(), &config);
+        gadget.absorb_many([FpVar::Constant(Fr::from(1u64)), FpVar::Constant(Fr::from(2u64))]);
+
+        assert_eq!(gadget.squeeze().value().unwrap(), native.squeeze());
+    }
+
+    #[test]
+    fn repeated_builds_skip_the_round_search() {
+        use ark_bls12_381::Fr;
+        let builder = PoseidonParamsBuilder::new().arity(6).security_level(100);
+        let first = builder.build::<Fr>().unwrap();
+
+        let search = std::time::Instant::now();
+        builder.round_numbers::<Fr>().unwrap();
+        let search = search.elapsed();
+
+        let hits = std::time::Instant::now();
+        for _ in 0..100 {
+            assert!(Arc::ptr_eq(&first, &builder.build::<Fr>().unwrap()));
+        }
+        assert!(hits.elapsed() < search);
+    }
+}
@@ -0,0 +1,285 @@
+//! Poseidon2 permutation and sponge wrappers.
+//!
+//! Poseidon2 (Grassi, Khovratovich, Schofnegger,
+//! <https://eprint.iacr.org/2023/323.pdf>) keeps Poseidon’s round
+//! structure but swaps the dense MDS for a cheap external matrix
+//! `M_E` in full rounds and `M_I = 1·1ᵀ + diag(d)` in partial rounds.
+//!
+//! Only `t = 2` and `t = 3` are supported: for those widths the paper
+//! fixes both matrices, whereas larger states need searched diagonals.
+//! Round constants are drawn from the Poseidon Grain LFSR in the same
+//! order as the reference implementation (`t` per full round, one per
+//! partial round), so on BN254 with `t = 3` the permutation matches
+//! HorizenLabs’ `poseidon2` test vectors.
+//!
+//! Linear layers cost nothing in R1CS, so the cheaper matrices speed
+//! up native hashing but not proving: constraint counts follow the
+//! number of S‑boxes per absorbed element, which at `t = 3` is higher
+//! than Poseidon at `t = 5`.
+//!
+//! [`Poseidon2Hash`] and [`Poseidon2HashVar`] are the generic
+//! [`DuplexSponge`] / [`DuplexSpongeVar`] over this permutation, so they
+//! mirror [`PoseidonHash`](super::PoseidonHash) and
+//! [`PoseidonHashVar`](super::PoseidonHashVar): same duplex semantics,
+//! capacity 1 at lane 0, `absorb_many` / `squeeze`.
+
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{FieldVar, fp::FpVar};
+use ark_relations::r1cs::SynthesisError;
+
+use super::params::{PoseidonParamsBuilder, PoseidonParamsError, smallest_alpha};
+use super::registry::{self, ParamsKey};
+use super::sponge::{DuplexSponge, DuplexSpongeVar, SpongePermutation};
+
+/// Poseidon2 configuration (capacity 1).
+#[derive(Clone, Debug)]
+pub struct Poseidon2Config<F: PrimeField> {
+    /// Number of full rounds `R_F`.
+    pub full_rounds: usize,
+    /// Number of partial rounds `R_P`.
+    pub partial_rounds: usize,
+    /// S‑box exponent.
+    pub alpha: u64,
+    /// Full‑round constants, indexed `[round][lane]`.
+    pub external_rc: Vec<Vec<F>>,
+    /// Partial‑round constants, added to lane 0.
+    pub internal_rc: Vec<F>,
+    /// Diagonal `d` of `M_I = 1·1ᵀ + diag(d)`.
+    pub internal_diag_m_1: Vec<F>,
+    /// The rate (in field elements).
+    pub rate: usize,
+    /// The capacity (in field elements).
+    pub capacity: usize,
+}
+
+impl PoseidonParamsBuilder {
+    /// Build (or fetch from the registry) a Poseidon2 configuration for `F`.
+    ///
+    /// Uses the same round numbers as Poseidon without rounding partial
+    /// rounds up to a multiple of `t`; only arities 1 and 2 are accepted.
+    /// As with [`build`](PoseidonParamsBuilder::build), a repeated build
+    /// is a registry lookup.
+    pub fn build_poseidon2<F: PrimeField>(
+        &self,
+    ) -> Result<Arc<Poseidon2Config<F>>, PoseidonParamsError> {
+        registry::get_or_try_init(*self, || {
+            let width = self.width();
+            let internal_diag_m_1: Vec<F> = match width {
+                2 => vec![F::from(1u64), F::from(2u64)],
+                3 => vec![F::from(1u64), F::from(1u64), F::from(2u64)],
+                _ => return Err(PoseidonParamsError::UnsupportedWidth(width)),
+            };
+            let (full_rounds, partial_rounds) = self.secure_round_numbers::<F>()?;
+            let alpha = self.sbox_exponent();
+            let key = ParamsKey {
+                width,
+                full_rounds,
+                partial_rounds,
+                alpha,
+            };
+
+            Ok(registry::get_or_init(key, || {
+                // the LFSR yields rows of `t` constants; the reference consumes them as one stream
+                let (ark, _) = find_poseidon_ark_and_mds::<F>(
+                    F::MODULUS_BIT_SIZE as u64,
+                    width - 1,
+                    full_rounds as u64,
+                    partial_rounds as u64,
+                    0, // seed
+                );
+                let mut stream = ark.into_iter().flatten();
+                let half = full_rounds / 2;
+                let mut external_rc = Vec::with_capacity(full_rounds);
+                let mut internal_rc = Vec::with_capacity(partial_rounds);
+                for round in 0..full_rounds + partial_rounds {
+                    if round < half || round >= half + partial_rounds {
+                        external_rc.push(stream.by_ref().take(width).collect());
+                    } else {
+                        internal_rc.extend(stream.next());
+                    }
+                }
+
+                Poseidon2Config {
+                    full_rounds,
+                    partial_rounds,
+                    alpha,
+                    external_rc,
+                    internal_rc,
+                    internal_diag_m_1,
+                    rate: width - 1,
+                    capacity: 1,
+                }
+            }))
+        })
+    }
+}
+
+/// Default Poseidon2 configuration: arity 2, 128‑bit security and the
+/// smallest S‑box exponent that permutes `F` (x^5 on BN254 and BLS12‑381).
+pub fn cached_poseidon2_config<F: PrimeField>() -> Arc<Poseidon2Config<F>> {
+    PoseidonParamsBuilder::new()
+        .arity(2)
+        .alpha(smallest_alpha::<F>())
+        .build_poseidon2::<F>()
+        .expect("default Poseidon2 parameters are valid for every prime field")
+}
+
+impl<F: PrimeField> SpongePermutation<F> for Poseidon2Config<F> {
+    fn cached_default() -> Arc<Self> {
+        cached_poseidon2_config::<F>()
+    }
+
+    fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        let half = self.full_rounds / 2;
+        external_matrix(state);
+        for rc in &self.external_rc[..half] {
+            for (x, c) in state.iter_mut().zip(rc) {
+                *x = (*x + c).pow([self.alpha]);
+            }
+            external_matrix(state);
+        }
+        for c in &self.internal_rc {
+            state[0] = (state[0] + c).pow([self.alpha]);
+            let sum: F = state.iter().sum();
+            for (x, d) in state.iter_mut().zip(&self.internal_diag_m_1) {
+                *x = *x * d + sum;
+            }
+        }
+        for rc in &self.external_rc[half..] {
+            for (x, c) in state.iter_mut().zip(rc) {
+                *x = (*x + c).pow([self.alpha]);
+            }
+            external_matrix(state);
+        }
+    }
+
+    // Linear layers are free in R1CS; only the S‑boxes cost constraints.
+    fn permute_var(&self, state: &mut [FpVar<F>]) -> Result<(), SynthesisError> {
+        let half = self.full_rounds / 2;
+        external_matrix_var(state);
+        for rc in &self.external_rc[..half] {
+            for (x, c) in state.iter_mut().zip(rc) {
+                *x = (&*x + *c).pow_by_constant([self.alpha])?;
+            }
+            external_matrix_var(state);
+        }
+        for c in &self.internal_rc {
+            state[0] = (&state[0] + *c).pow_by_constant([self.alpha])?;
+            let sum: FpVar<F> = state.iter().sum();
+            for (x, d) in state.iter_mut().zip(&self.internal_diag_m_1) {
+                *x = &*x * *d + &sum;
+            }
+        }
+        for rc in &self.external_rc[half..] {
+            for (x, c) in state.iter_mut().zip(rc) {
+                *x = (&*x + *c).pow_by_constant([self.alpha])?;
+            }
+            external_matrix_var(state);
+        }
+        Ok(())
+    }
+}
+
+/// `M_E = circ(2, 1)` / `circ(2, 1, 1)`, i.e. `x_i + Σx`.
+fn external_matrix<F: PrimeField>(state: &mut [F]) {
+    let sum: F = state.iter().sum();
+    state.iter_mut().for_each(|x| *x += sum);
+}
+
+fn external_matrix_var<F: PrimeField>(state: &mut [FpVar<F>]) {
+    let sum: FpVar<F> = state.iter().sum();
+    state.iter_mut().for_each(|x| *x += &sum);
+}
+
+/// Native‑field Poseidon2 hash helper.
+pub type Poseidon2Hash<F> = DuplexSponge<F, Poseidon2Config<F>>;
+
+/// Constraint‑system variant of `Poseidon2Hash`.
+pub type Poseidon2HashVar<F> = DuplexSpongeVar<F, Poseidon2Config<F>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, Field};
+    use ark_r1cs_std::{R1CSVar, alloc::AllocVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn hex(f: Fr) -> String {
+        f.into_bigint()
+            .to_bytes_be()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// HorizenLabs `poseidon2` BN254, t = 3: permutation of `[0, 1, 2]`.
+    #[test]
+    fn permutation_matches_reference_vector() {
+        let config = cached_poseidon2_config::<Fr>();
+        assert!(Arc::ptr_eq(&config, &cached_poseidon2_config::<Fr>()));
+        assert_eq!(
+            (config.full_rounds, config.partial_rounds, config.alpha),
+            (8, 56, 5)
+        );
+        assert_eq!(
+            hex(config.external_rc[0][0]),
+            "1d066a255517b7fd8bddd3a93f7804ef7f8fcde48bb4c37a59a09a1a97052816"
+        );
+
+        let mut state = [Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+        config.permute(&mut state);
+        assert_eq!(
+            state.map(hex),
+            [
+                "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+                "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+                "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+            ]
+        );
+    }
+
+    #[test]
+    fn poseidon2_native_vs_r1cs() {
+        let inputs: Vec<Fr> = (1u64..=5).map(Fr::from).collect();
+        let mut native = Poseidon2Hash::<Fr>::new();
+        native.absorb_many(inputs.iter());
+
+        let cs = ConstraintSystem::new_ref();
+        let vars: Vec<FpVar<Fr>> = inputs
+            .iter()
+            .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+            .collect();
+        let mut gadget = Poseidon2HashVar::new(cs.clone());
+        gadget.absorb_many(vars.iter());
+
+        for _ in 0..3 {
+            assert_eq!(gadget.squeeze().value().unwrap(), native.squeeze());
+        }
+        assert!(cs.is_satisfied().unwrap());
+
+        // absorbing after squeezing keeps both sides in lockstep
+        native.absorb_many([Fr::ONE]);
+        gadget.absorb_many([FpVar::Constant(Fr::ONE)]);
+        assert_eq!(gadget.squeeze().value().unwrap(), native.squeeze());
+    }
+
+    #[test]
+    fn rejects_widths_without_fixed_matrices() {
+        let err = PoseidonParamsBuilder::new()
+            .build_poseidon2::<Fr>()
+            .unwrap_err();
+        assert_eq!(err, PoseidonParamsError::UnsupportedWidth(5));
+    }
+}
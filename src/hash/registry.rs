@@ -0,0 +1,144 @@
+//! Process‑wide cache of Poseidon parameters.
+//!
+//! Generating round constants runs the Grain LFSR over every
+//! round, which dominates the cost of creating a fresh sponge.
+//! The registry keeps one `Arc`‑shared configuration per
+//! configuration type (which fixes the field) and [`ParamsKey`]
+//! (state width plus round shape), built lazily on first use and
+//! shared across threads afterwards. Builders additionally record the
+//! finished configuration under their own inputs
+//! ([`PoseidonParamsBuilder`]), so a repeated build skips the round‑number
+//! search as well.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+use super::params::PoseidonParamsBuilder;
+
+type Entry = Arc<dyn Any + Send + Sync>;
+type Slot = Arc<OnceLock<Entry>>;
+
+/// Shape identifying a Grain‑LFSR generated configuration within one field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ParamsKey {
+    /// State width `t = rate + capacity`.
+    pub width: usize,
+    /// Number of full rounds `R_F`.
+    pub full_rounds: usize,
+    /// Number of partial rounds `R_P`.
+    pub partial_rounds: usize,
+    /// S‑box exponent.
+    pub alpha: u64,
+}
+
+/// What a configuration is filed under: the shape it was generated with,
+/// or the builder inputs it was derived from.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+    Shape(ParamsKey),
+    Inputs(PoseidonParamsBuilder),
+}
+
+fn registry() -> &'static RwLock<HashMap<(TypeId, Key), Slot>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<(TypeId, Key), Slot>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// The slot for `key`, inserted empty if absent. The map lock is only
+/// held for the lookup, and since nothing can panic while it is held a
+/// poisoned lock still guards a consistent map.
+fn slot(key: (TypeId, Key)) -> Slot {
+    let found = registry()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&key)
+        .cloned();
+    found.unwrap_or_else(|| {
+        let mut map = registry().write().unwrap_or_else(PoisonError::into_inner);
+        map.entry(key).or_default().clone()
+    })
+}
+
+/// Returns the cached configuration of type `C` (e.g. `PoseidonConfig<F>`)
+/// with shape `params`, building it with `init` if this is the first request.
+///
+/// `init` runs outside the map lock and at most once per key: concurrent
+/// callers for the same key block until the winning thread is done, while
+/// other keys proceed. If `init` panics the slot stays empty and the next
+/// caller runs it again.
+pub fn get_or_init<C, I>(params: ParamsKey, init: I) -> Arc<C>
+where
+    C: Any + Send + Sync,
+    I: FnOnce() -> C,
+{
+    let slot = slot((TypeId::of::<C>(), Key::Shape(params)));
+    downcast(slot.get_or_init(|| Arc::new(init())).clone())
+}
+
+/// Returns the configuration of type `C` previously built from `inputs`,
+/// or derives it with `init` (which is expected to go through
+/// [`get_or_init`]). Failures are not cached.
+///
+/// Unlike [`get_or_init`], two threads missing at once may both run
+/// `init`; they end up with the same `Arc` from the shape entry.
+pub(crate) fn get_or_try_init<C, E, I>(inputs: PoseidonParamsBuilder, init: I) -> Result<Arc<C>, E>
+where
+    C: Any + Send + Sync,
+    I: FnOnce() -> Result<Arc<C>, E>,
+{
+    let slot = slot((TypeId::of::<C>(), Key::Inputs(inputs)));
+    if let Some(entry) = slot.get() {
+        return Ok(downcast(entry.clone()));
+    }
+    let config = init()?;
+    Ok(downcast(slot.get_or_init(|| config).clone()))
+}
+
+fn downcast<C: Any + Send + Sync>(entry: Entry) -> Arc<C> {
+    entry
+        .downcast::<C>()
+        .expect("registry key is bound to the configuration type")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::cached_poseidon_config;
+    use std::panic;
+    use std::thread;
+
+    #[test]
+    fn shared_per_field_and_thread_safe() {
+        let first = cached_poseidon_config::<ark_bn254::Fr>();
+        let handles: Vec<_> = (0..4)
+            .map(|_| thread::spawn(cached_poseidon_config::<ark_bn254::Fr>))
+            .collect();
+        for handle in handles {
+            assert!(Arc::ptr_eq(&first, &handle.join().unwrap()));
+        }
+
+        // a different field gets its own entry instead of a mistyped hit
+        let other = cached_poseidon_config::<ark_bls12_381::Fr>();
+        assert_eq!(other.rate, first.rate);
+    }
+
+    #[test]
+    fn panicking_init_leaves_the_key_usable() {
+        struct Probe(u32);
+        let key = ParamsKey {
+            width: 0,
+            full_rounds: 0,
+            partial_rounds: 0,
+            alpha: 0,
+        };
+
+        let failed = panic::catch_unwind(|| get_or_init::<Probe, _>(key, || panic!("init failed")));
+        assert!(failed.is_err());
+
+        // neither the key nor the rest of the registry is wedged
+        assert_eq!(get_or_init(key, || Probe(7)).0, 7);
+        assert_eq!(get_or_init(key, || Probe(8)).0, 7);
+        assert_eq!(cached_poseidon_config::<ark_bn254::Fr>().rate, 4);
+    }
+}
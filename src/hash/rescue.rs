@@ -0,0 +1,226 @@
+//! Rescue‑Prime permutation and sponge wrappers.
+//!
+//! Each of the `N` rounds of Rescue‑Prime (Szepieniec, Ashur, Dhooghe,
+//! <https://eprint.iacr.org/2020/1143.pdf>) applies `x^alpha`, the MDS
+//! and a constant vector, then `x^{1/alpha}`, the MDS and a second
+//! constant vector. In R1CS the inverse S‑box costs the same as the
+//! forward one (the root is a witness checked by `y^alpha = x`), which
+//! keeps the round count – and constraint count – low, while native
+//! hashing pays a full‑size exponentiation per lane and round.
+//!
+//! `N` follows the reference `get_number_of_rounds` (Gröbner basis
+//! bound, at least 5 rounds, plus 50%). Constants and the Cauchy MDS
+//! are drawn from the Poseidon Grain LFSR instead of SHAKE256, so
+//! digests differ from the reference implementation.
+
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{FieldVar, fp::FpVar};
+use ark_relations::r1cs::SynthesisError;
+
+use super::params::{
+    PoseidonParamsBuilder, PoseidonParamsError, inverse_alpha, log2_binomial, smallest_alpha,
+};
+use super::registry::{self, ParamsKey};
+use super::sponge::{DuplexSponge, DuplexSpongeVar, SpongePermutation, inverse_sbox_var};
+
+/// Rescue‑Prime configuration (capacity 1).
+#[derive(Clone, Debug)]
+pub struct RescuePrimeConfig<F: PrimeField> {
+    /// Number of (double) rounds `N`.
+    pub rounds: usize,
+    /// S‑box exponent.
+    pub alpha: u64,
+    /// Little‑endian limbs of `alpha^{-1} mod (p - 1)`.
+    pub alpha_inv: Vec<u64>,
+    /// MDS matrix.
+    pub mds: Vec<Vec<F>>,
+    /// Round constants, two vectors per round, indexed `[2 * round + step][lane]`.
+    pub round_constants: Vec<Vec<F>>,
+    /// The rate (in field elements).
+    pub rate: usize,
+    /// The capacity (in field elements).
+    pub capacity: usize,
+}
+
+impl PoseidonParamsBuilder {
+    /// Returns the number of Rescue‑Prime rounds for the field `F`.
+    pub fn rescue_prime_rounds<F: PrimeField>(&self) -> Result<usize, PoseidonParamsError> {
+        self.validate::<F>()?;
+        let m = self.width() as f64;
+        let rate = m - 1.0;
+        let alpha = self.sbox_exponent() as f64;
+        let security = self.target_security_level() as f64;
+
+        let l1 = (1..25)
+            .find(|&l| {
+                let l = l as f64;
+                let v = m * (l - 1.0) + rate;
+                let dcon = (0.5 * (alpha - 1.0) * m * (l - 1.0) + 2.0).floor();
+                2.0 * log2_binomial(v + dcon, v) > security
+            })
+            .ok_or(PoseidonParamsError::NoRoundNumbers)?;
+        Ok((1.5 * l1.max(5) as f64).ceil() as usize)
+    }
+
+    /// Build (or fetch from the registry) a Rescue‑Prime configuration for `F`.
+    pub fn build_rescue_prime<F: PrimeField>(
+        &self,
+    ) -> Result<Arc<RescuePrimeConfig<F>>, PoseidonParamsError> {
+        registry::get_or_try_init(*self, || {
+            let rounds = self.rescue_prime_rounds::<F>()?;
+            let width = self.width();
+            let alpha = self.sbox_exponent();
+            let key = ParamsKey {
+                width,
+                full_rounds: 2 * rounds,
+                partial_rounds: 0,
+                alpha,
+            };
+
+            Ok(registry::get_or_init(key, || {
+                let (round_constants, mds) = find_poseidon_ark_and_mds::<F>(
+                    F::MODULUS_BIT_SIZE as u64,
+                    width - 1,
+                    2 * rounds as u64,
+                    0,
+                    0, // seed
+                );
+                RescuePrimeConfig {
+                    rounds,
+                    alpha,
+                    alpha_inv: inverse_alpha::<F>(alpha),
+                    mds,
+                    round_constants,
+                    rate: width - 1,
+                    capacity: 1,
+                }
+            }))
+        })
+    }
+}
+
+/// Default Rescue‑Prime configuration: arity 2, 128‑bit security and the
+/// smallest S‑box exponent that permutes `F`.
+pub fn cached_rescue_prime_config<F: PrimeField>() -> Arc<RescuePrimeConfig<F>> {
+    PoseidonParamsBuilder::new()
+        .arity(2)
+        .alpha(smallest_alpha::<F>())
+        .build_rescue_prime::<F>()
+        .expect("default Rescue-Prime parameters are valid for every prime field")
+}
+
+impl<F: PrimeField> SpongePermutation<F> for RescuePrimeConfig<F> {
+    fn cached_default() -> Arc<Self> {
+        cached_rescue_prime_config::<F>()
+    }
+
+    fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        for constants in self.round_constants.chunks(2) {
+            state.iter_mut().for_each(|x| *x = x.pow([self.alpha]));
+            self.affine(state, &constants[0]);
+            state.iter_mut().for_each(|x| *x = x.pow(&self.alpha_inv));
+            self.affine(state, &constants[1]);
+        }
+    }
+
+    fn permute_var(&self, state: &mut [FpVar<F>]) -> Result<(), SynthesisError> {
+        for constants in self.round_constants.chunks(2) {
+            for x in state.iter_mut() {
+                *x = x.pow_by_constant([self.alpha])?;
+            }
+            self.affine_var(state, &constants[0]);
+            for x in state.iter_mut() {
+                *x = inverse_sbox_var(x, self.alpha, &self.alpha_inv)?;
+            }
+            self.affine_var(state, &constants[1]);
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> RescuePrimeConfig<F> {
+    /// `state <- MDS * state + constants`.
+    fn affine(&self, state: &mut [F], constants: &[F]) {
+        let new_state: Vec<F> = self
+            .mds
+            .iter()
+            .zip(constants)
+            .map(|(row, c)| row.iter().zip(state.iter()).map(|(m, x)| *m * x).sum::<F>() + c)
+            .collect();
+        state.copy_from_slice(&new_state);
+    }
+
+    fn affine_var(&self, state: &mut [FpVar<F>], constants: &[F]) {
+        let new_state: Vec<FpVar<F>> = self
+            .mds
+            .iter()
+            .zip(constants)
+            .map(|(row, c)| {
+                row.iter()
+                    .zip(state.iter())
+                    .map(|(m, x)| x * *m)
+                    .sum::<FpVar<F>>()
+                    + *c
+            })
+            .collect();
+        state.clone_from_slice(&new_state);
+    }
+}
+
+/// Native‑field Rescue‑Prime hash helper.
+pub type RescuePrimeHash<F> = DuplexSponge<F, RescuePrimeConfig<F>>;
+
+/// Constraint‑system variant of `RescuePrimeHash`.
+pub type RescuePrimeHashVar<F> = DuplexSpongeVar<F, RescuePrimeConfig<F>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::{Field, UniformRand};
+    use ark_r1cs_std::{R1CSVar, alloc::AllocVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    #[test]
+    fn inverse_sbox_inverts_alpha() {
+        let mut rng = test_rng();
+        let config = cached_rescue_prime_config::<Fr>();
+        assert!(Arc::ptr_eq(&config, &cached_rescue_prime_config::<Fr>()));
+        for _ in 0..4 {
+            let x = Fr::rand(&mut rng);
+            assert_eq!(x.pow(&config.alpha_inv).pow([config.alpha]), x);
+        }
+    }
+
+    #[test]
+    fn rescue_prime_native_vs_r1cs() {
+        let inputs: Vec<Fr> = (1u64..=5).map(Fr::from).collect();
+        let mut native = RescuePrimeHash::<Fr>::new();
+        native.absorb_many(inputs.iter());
+
+        let cs = ConstraintSystem::new_ref();
+        let vars: Vec<FpVar<Fr>> = inputs
+            .iter()
+            .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+            .collect();
+        let mut gadget = RescuePrimeHashVar::new(cs.clone());
+        gadget.absorb_many(vars.iter());
+
+        for _ in 0..3 {
+            assert_eq!(gadget.squeeze().value().unwrap(), native.squeeze());
+        }
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
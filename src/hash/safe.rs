@@ -0,0 +1,412 @@
+//! SAFE (Sponge API for Field Elements) over the Poseidon sponge.
+//!
+//! SAFE (Aumasson et al., <https://eprint.iacr.org/2023/522.pdf>) makes
+//! the caller declare up front the sequence of absorb / squeeze calls a
+//! sponge will see – its IO pattern – and derives the domain tag from
+//! that pattern:
+//!
+//! 1. consecutive operations of the same kind are aggregated;
+//! 2. each operation becomes a 32‑bit word, `0x8000_0000 | n` for
+//!    `absorb(n)` and `n` for `squeeze(n)`, serialized big‑endian;
+//! 3. the caller’s domain separator bytes are appended, the string is
+//!    hashed with SHA3‑256 and the first 128 bits, read big‑endian, are
+//!    the tag written to the capacity element.
+//!
+//! [`SafeSponge`] and [`SafeSpongeVar`] check every call against the
+//! pattern and return [`SafeError`] on any deviation, so a transcript
+//! built natively and one built in‑circuit are guaranteed to follow the
+//! same schedule. Absorbing and squeezing keep the duplex semantics of
+//! [`PoseidonHash`]; only the initial state is SAFE‑specific.
+
+use std::fmt;
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use super::{PoseidonHash, PoseidonHashVar, cached_poseidon_config};
+use crate::digest::sha3_256;
+
+/// A single SAFE operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpongeOp {
+    /// Absorb this many field elements.
+    Absorb(u32),
+    /// Squeeze this many field elements.
+    Squeeze(u32),
+}
+
+impl SpongeOp {
+    fn len(self) -> u32 {
+        match self {
+            Self::Absorb(n) | Self::Squeeze(n) => n,
+        }
+    }
+
+    fn with_len(self, n: u32) -> Self {
+        match self {
+            Self::Absorb(_) => Self::Absorb(n),
+            Self::Squeeze(_) => Self::Squeeze(n),
+        }
+    }
+
+    fn same_kind(self, other: Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Absorb(_), Self::Absorb(_)) | (Self::Squeeze(_), Self::Squeeze(_))
+        )
+    }
+
+    /// SAFE word encoding of the operation.
+    fn word(self) -> u32 {
+        match self {
+            Self::Absorb(n) => 0x8000_0000 | n,
+            Self::Squeeze(n) => n,
+        }
+    }
+}
+
+/// Declared sequence of absorb / squeeze operations.
+///
+/// Consecutive operations of the same kind are merged and empty ones are
+/// dropped, so `absorb(1).absorb(2)` and `absorb(3)` describe the same
+/// pattern.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IoPattern {
+    ops: Vec<SpongeOp>,
+}
+
+impl IoPattern {
+    /// An empty pattern.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `absorb(n)`.
+    pub fn absorb(self, n: u32) -> Self {
+        self.push(SpongeOp::Absorb(n))
+    }
+
+    /// Append `squeeze(n)`.
+    pub fn squeeze(self, n: u32) -> Self {
+        self.push(SpongeOp::Squeeze(n))
+    }
+
+    /// The aggregated operations.
+    pub fn ops(&self) -> &[SpongeOp] {
+        &self.ops
+    }
+
+    /// Domain tag for this pattern and `domain_separator`.
+    pub fn tag<F: PrimeField>(&self, domain_separator: &[u8]) -> F {
+        let mut bytes: Vec<u8> = self
+            .ops
+            .iter()
+            .flat_map(|op| op.word().to_be_bytes())
+            .collect();
+        bytes.extend_from_slice(domain_separator);
+        F::from_be_bytes_mod_order(&sha3_256(&bytes)[..16])
+    }
+
+    fn push(mut self, op: SpongeOp) -> Self {
+        if op.len() == 0 {
+            return self;
+        }
+        match self.ops.last_mut() {
+            Some(last) if last.same_kind(op) => *last = last.with_len(last.len() + op.len()),
+            _ => self.ops.push(op),
+        }
+        self
+    }
+}
+
+/// Errors returned when a SAFE sponge deviates from its IO pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SafeError {
+    /// A call does not fit the next operation of the pattern
+    /// (`expected` is `None` once the pattern is exhausted).
+    PatternMismatch {
+        expected: Option<SpongeOp>,
+        found: SpongeOp,
+    },
+    /// `finish` was called with operations left in the pattern.
+    Unfinished(Vec<SpongeOp>),
+    /// Constraint synthesis failed.
+    Synthesis(SynthesisError),
+}
+
+impl fmt::Display for SafeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PatternMismatch {
+                expected: Some(expected),
+                found,
+            } => write!(f, "expected {expected:?}, found {found:?}"),
+            Self::PatternMismatch {
+                expected: None,
+                found,
+            } => write!(f, "IO pattern exhausted, found {found:?}"),
+            Self::Unfinished(ops) => write!(f, "IO pattern not finished: {ops:?} left"),
+            Self::Synthesis(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SafeError {}
+
+impl From<SynthesisError> for SafeError {
+    fn from(err: SynthesisError) -> Self {
+        Self::Synthesis(err)
+    }
+}
+
+/// Position inside an [`IoPattern`].
+#[derive(Clone, Debug)]
+struct Cursor {
+    pattern: IoPattern,
+    op: usize,
+    used: u32,
+}
+
+impl Cursor {
+    fn new(pattern: &IoPattern) -> Self {
+        Self {
+            pattern: pattern.clone(),
+            op: 0,
+            used: 0,
+        }
+    }
+
+    /// The rest of the current operation, if any.
+    fn expected(&self) -> Option<SpongeOp> {
+        let op = *self.pattern.ops.get(self.op)?;
+        Some(op.with_len(op.len() - self.used))
+    }
+
+    /// Check `found` against the pattern and advance past it.
+    fn consume(&mut self, found: SpongeOp) -> Result<(), SafeError> {
+        if found.len() == 0 {
+            return Ok(());
+        }
+        let expected = self.expected();
+        match expected {
+            Some(op) if op.same_kind(found) && found.len() <= op.len() => {
+                self.used += found.len();
+                if found.len() == op.len() {
+                    self.op += 1;
+                    self.used = 0;
+                }
+                Ok(())
+            }
+            _ => Err(SafeError::PatternMismatch { expected, found }),
+        }
+    }
+
+    fn finish(&self) -> Result<(), SafeError> {
+        match self.expected() {
+            None => Ok(()),
+            Some(op) => {
+                let mut left = vec![op];
+                left.extend_from_slice(&self.pattern.ops[self.op + 1..]);
+                Err(SafeError::Unfinished(left))
+            }
+        }
+    }
+}
+
+fn len(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+/// Native SAFE sponge.
+#[derive(Clone)]
+pub struct SafeSponge<F: Absorb + PrimeField> {
+    hash: PoseidonHash<F>,
+    cursor: Cursor,
+}
+
+impl<F: Absorb + PrimeField> SafeSponge<F> {
+    /// Start a sponge over the canonical parameters for `pattern`.
+    pub fn new(pattern: &IoPattern, domain_separator: &[u8]) -> Self {
+        Self::with_config(&cached_poseidon_config::<F>(), pattern, domain_separator)
+    }
+
+    /// Start a sponge over an explicit configuration.
+    pub fn with_config(
+        config: &PoseidonConfig<F>,
+        pattern: &IoPattern,
+        domain_separator: &[u8],
+    ) -> Self {
+        Self {
+            hash: PoseidonHash::with_config_and_domain(config, pattern.tag(domain_separator)),
+            cursor: Cursor::new(pattern),
+        }
+    }
+
+    /// Absorb `inputs`; must fit the next `absorb` of the pattern.
+    pub fn absorb(&mut self, inputs: &[F]) -> Result<(), SafeError> {
+        self.cursor.consume(SpongeOp::Absorb(len(inputs.len())))?;
+        self.hash.absorb_many(inputs.iter());
+        Ok(())
+    }
+
+    /// Squeeze `n` elements; must fit the next `squeeze` of the pattern.
+    pub fn squeeze(&mut self, n: usize) -> Result<Vec<F>, SafeError> {
+        self.cursor.consume(SpongeOp::Squeeze(len(n)))?;
+        Ok((0..n).map(|_| self.hash.squeeze()).collect())
+    }
+
+    /// Check that the whole pattern was consumed.
+    pub fn finish(self) -> Result<(), SafeError> {
+        self.cursor.finish()
+    }
+}
+
+/// Constraint‑system variant of [`SafeSponge`].
+#[derive(Clone)]
+pub struct SafeSpongeVar<F: Absorb + PrimeField> {
+    hash: PoseidonHashVar<F>,
+    cursor: Cursor,
+}
+
+impl<F: Absorb + PrimeField> SafeSpongeVar<F> {
+    /// Start a sponge gadget over the canonical parameters for `pattern`.
+    pub fn new(cs: ConstraintSystemRef<F>, pattern: &IoPattern, domain_separator: &[u8]) -> Self {
+        Self::with_config(
+            cs,
+            &cached_poseidon_config::<F>(),
+            pattern,
+            domain_separator,
+        )
+    }
+
+    /// Start a sponge gadget over an explicit configuration.
+    pub fn with_config(
+        cs: ConstraintSystemRef<F>,
+        config: &PoseidonConfig<F>,
+        pattern: &IoPattern,
+        domain_separator: &[u8],
+    ) -> Self {
+        Self {
+            hash: PoseidonHashVar::with_config_and_domain(
+                cs,
+                config,
+                pattern.tag(domain_separator),
+            ),
+            cursor: Cursor::new(pattern),
+        }
+    }
+
+    /// Absorb `inputs`; must fit the next `absorb` of the pattern.
+    pub fn absorb(&mut self, inputs: &[FpVar<F>]) -> Result<(), SafeError> {
+        self.cursor.consume(SpongeOp::Absorb(len(inputs.len())))?;
+        self.hash.try_absorb_many(inputs.iter())?;
+        Ok(())
+    }
+
+    /// Squeeze `n` elements; must fit the next `squeeze` of the pattern.
+    pub fn squeeze(&mut self, n: usize) -> Result<Vec<FpVar<F>>, SafeError> {
+        self.cursor.consume(SpongeOp::Squeeze(len(n)))?;
+        Ok((0..n)
+            .map(|_| self.hash.try_squeeze())
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Check that the whole pattern was consumed.
+    pub fn finish(self) -> Result<(), SafeError> {
+        self.cursor.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::Field;
+    use ark_r1cs_std::{R1CSVar, alloc::AllocVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn pattern_aggregation_and_tags() {
+        let split = IoPattern::new().absorb(1).absorb(2).squeeze(0).squeeze(1);
+        let merged = IoPattern::new().absorb(3).squeeze(1);
+        assert_eq!(split, merged);
+        assert_eq!(merged.ops(), [SpongeOp::Absorb(3), SpongeOp::Squeeze(1)]);
+
+        let tag = merged.tag::<Fr>(b"report");
+        assert_ne!(tag, merged.tag::<Fr>(b"nullifier"));
+        assert_ne!(
+            tag,
+            IoPattern::new().absorb(2).squeeze(1).tag::<Fr>(b"report")
+        );
+        assert!(tag < Fr::from(2u64).pow([128]));
+    }
+
+    #[test]
+    fn native_and_gadget_follow_the_pattern() {
+        let pattern = IoPattern::new().absorb(3).squeeze(2).absorb(1).squeeze(1);
+        let inputs = [1u64, 2, 3, 4].map(Fr::from);
+
+        let mut native = SafeSponge::<Fr>::new(&pattern, b"test");
+        native.absorb(&inputs[..1]).unwrap();
+        native.absorb(&inputs[1..3]).unwrap();
+        let mut expected = native.squeeze(2).unwrap();
+        native.absorb(&inputs[3..]).unwrap();
+        expected.extend(native.squeeze(1).unwrap());
+        native.finish().unwrap();
+
+        let cs = ConstraintSystem::new_ref();
+        let vars = inputs.map(|x| FpVar::new_witness(cs.clone(), || Ok(x)).unwrap());
+        let mut gadget = SafeSpongeVar::new(cs.clone(), &pattern, b"test");
+        gadget.absorb(&vars[..3]).unwrap();
+        let mut outputs = gadget.squeeze(2).unwrap();
+        gadget.absorb(&vars[3..]).unwrap();
+        outputs.extend(gadget.squeeze(1).unwrap());
+        gadget.finish().unwrap();
+
+        assert_eq!(outputs.value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn deviations_are_rejected() {
+        let pattern = IoPattern::new().absorb(2).squeeze(1);
+        let one = Fr::from(1u64);
+
+        let mut sponge = SafeSponge::<Fr>::new(&pattern, b"");
+        assert_eq!(
+            sponge.squeeze(1),
+            Err(SafeError::PatternMismatch {
+                expected: Some(SpongeOp::Absorb(2)),
+                found: SpongeOp::Squeeze(1),
+            })
+        );
+        assert_eq!(
+            sponge.absorb(&[one; 3]),
+            Err(SafeError::PatternMismatch {
+                expected: Some(SpongeOp::Absorb(2)),
+                found: SpongeOp::Absorb(3),
+            })
+        );
+        sponge.absorb(&[one]).unwrap();
+        assert_eq!(
+            sponge.clone().finish(),
+            Err(SafeError::Unfinished(vec![
+                SpongeOp::Absorb(1),
+                SpongeOp::Squeeze(1)
+            ]))
+        );
+        sponge.absorb(&[one]).unwrap();
+        sponge.squeeze(1).unwrap();
+        assert_eq!(
+            sponge.absorb(&[one]),
+            Err(SafeError::PatternMismatch {
+                expected: None,
+                found: SpongeOp::Absorb(1),
+            })
+        );
+        sponge.finish().unwrap();
+    }
+}
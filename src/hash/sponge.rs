@@ -0,0 +1,274 @@
+//! Generic duplex sponge over a pluggable permutation.
+//!
+//! [`DuplexSponge`] and [`DuplexSpongeVar`] follow the exact duplex
+//! semantics of Arkworks’ `PoseidonSponge` (capacity lanes first,
+//! absorb by addition into the rate, permute lazily on the next
+//! absorb/squeeze), so every permutation plugged in here behaves like
+//! [`PoseidonHash`](super::PoseidonHash) from the caller’s side.
+
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::constraints::AbsorbGadget;
+use ark_crypto_primitives::sponge::{Absorb, DuplexSpongeMode};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    R1CSVar,
+    alloc::AllocVar,
+    eq::EqGadget,
+    fields::{FieldVar, fp::FpVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+/// A permutation usable inside [`DuplexSponge`] and [`DuplexSpongeVar`].
+pub trait SpongePermutation<F: PrimeField>: Send + Sync + 'static {
+    /// Shared default parameters for this permutation.
+    fn cached_default() -> Arc<Self>;
+
+    /// Number of rate lanes.
+    fn rate(&self) -> usize;
+
+    /// Number of capacity lanes (placed before the rate).
+    fn capacity(&self) -> usize;
+
+    /// Apply the permutation to a native state in place.
+    fn permute(&self, state: &mut [F]);
+
+    /// Apply the permutation to a state of gadgets in place.
+    fn permute_var(&self, state: &mut [FpVar<F>]) -> Result<(), SynthesisError>;
+}
+
+/// Native duplex sponge driven by the permutation `P`.
+pub struct DuplexSponge<F: PrimeField, P: SpongePermutation<F>> {
+    pub config: Arc<P>,
+    pub state: Vec<F>,
+    pub mode: DuplexSpongeMode,
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> Clone for DuplexSponge<F, P> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            state: self.state.clone(),
+            mode: self.mode.clone(),
+        }
+    }
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> DuplexSponge<F, P> {
+    /// Construct a new sponge initialized with the default parameters.
+    pub fn new() -> Self {
+        Self::with_config(P::cached_default())
+    }
+
+    /// Construct a new sponge over an explicit configuration.
+    pub fn with_config(config: Arc<P>) -> Self {
+        Self {
+            state: vec![F::zero(); config.rate() + config.capacity()],
+            mode: DuplexSpongeMode::Absorbing {
+                next_absorb_index: 0,
+            },
+            config,
+        }
+    }
+
+    /// Construct a new sponge whose first capacity lane is set to `domain`.
+    pub fn with_domain(domain: F) -> Self {
+        Self::with_config_and_domain(P::cached_default(), domain)
+    }
+
+    /// Construct a domain‑separated sponge over an explicit configuration.
+    pub fn with_config_and_domain(config: Arc<P>, domain: F) -> Self {
+        let mut sponge = Self::with_config(config);
+        sponge.state[0] = domain;
+        sponge
+    }
+
+    /// Absorb an iterator of field elements
+    pub fn absorb_many<I, A>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: Absorb,
+    {
+        for elem in iter {
+            for f in elem.to_sponge_field_elements_as_vec::<F>() {
+                let index = match self.mode {
+                    DuplexSpongeMode::Absorbing { next_absorb_index }
+                        if next_absorb_index < self.config.rate() =>
+                    {
+                        next_absorb_index
+                    }
+                    DuplexSpongeMode::Absorbing { .. } => {
+                        self.config.permute(&mut self.state);
+                        0
+                    }
+                    DuplexSpongeMode::Squeezing { .. } => 0,
+                };
+                self.state[self.config.capacity() + index] += f;
+                self.mode = DuplexSpongeMode::Absorbing {
+                    next_absorb_index: index + 1,
+                };
+            }
+        }
+    }
+
+    /// Squeeze `one` field element from the sponge.
+    pub fn squeeze(&mut self) -> F {
+        let index = match self.mode {
+            DuplexSpongeMode::Squeezing { next_squeeze_index }
+                if next_squeeze_index < self.config.rate() =>
+            {
+                next_squeeze_index
+            }
+            _ => {
+                self.config.permute(&mut self.state);
+                0
+            }
+        };
+        self.mode = DuplexSpongeMode::Squeezing {
+            next_squeeze_index: index + 1,
+        };
+        self.state[self.config.capacity() + index]
+    }
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> Default for DuplexSponge<F, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Constraint‑system variant of `DuplexSponge`.
+pub struct DuplexSpongeVar<F: PrimeField, P: SpongePermutation<F>> {
+    cs: ConstraintSystemRef<F>,
+    config: Arc<P>,
+    state: Vec<FpVar<F>>,
+    mode: DuplexSpongeMode,
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> Clone for DuplexSpongeVar<F, P> {
+    fn clone(&self) -> Self {
+        Self {
+            cs: self.cs.clone(),
+            config: self.config.clone(),
+            state: self.state.clone(),
+            mode: self.mode.clone(),
+        }
+    }
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> DuplexSpongeVar<F, P> {
+    /// Create a fresh sponge gadget inside the given constraint system.
+    pub fn new(cs: ConstraintSystemRef<F>) -> Self {
+        Self::with_config(cs, P::cached_default())
+    }
+
+    /// Create a fresh sponge gadget over an explicit configuration.
+    pub fn with_config(cs: ConstraintSystemRef<F>, config: Arc<P>) -> Self {
+        Self {
+            cs,
+            state: vec![FpVar::zero(); config.rate() + config.capacity()],
+            mode: DuplexSpongeMode::Absorbing {
+                next_absorb_index: 0,
+            },
+            config,
+        }
+    }
+
+    /// Create a sponge gadget whose first capacity lane is the constant `domain`.
+    pub fn with_domain(cs: ConstraintSystemRef<F>, domain: F) -> Self {
+        Self::with_config_and_domain(cs, P::cached_default(), domain)
+    }
+
+    /// Create a domain‑separated sponge gadget over an explicit configuration.
+    pub fn with_config_and_domain(cs: ConstraintSystemRef<F>, config: Arc<P>, domain: F) -> Self {
+        let mut sponge = Self::with_config(cs, config);
+        sponge.state[0] = FpVar::Constant(domain);
+        sponge
+    }
+
+    /// Constraint system the sponge allocates into.
+    pub fn cs(&self) -> ConstraintSystemRef<F> {
+        self.cs.clone()
+    }
+
+    /// Absorb field gadgets.
+    ///
+    /// Panicking wrapper around [`Self::try_absorb_many`].
+    pub fn absorb_many<I, A>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: AbsorbGadget<F>,
+    {
+        self.try_absorb_many(iter)
+            .expect("Error while sponge absorbing");
+    }
+
+    /// Squeeze `one` element.
+    ///
+    /// Panicking wrapper around [`Self::try_squeeze`].
+    pub fn squeeze(&mut self) -> FpVar<F> {
+        self.try_squeeze().expect("Error while sponge squeezing")
+    }
+
+    /// Absorb field gadgets, returning the first synthesis error.
+    pub fn try_absorb_many<I, A>(&mut self, iter: I) -> Result<(), SynthesisError>
+    where
+        I: IntoIterator<Item = A>,
+        A: AbsorbGadget<F>,
+    {
+        for elem in iter {
+            for f in elem.to_sponge_field_elements()? {
+                let index = match self.mode {
+                    DuplexSpongeMode::Absorbing { next_absorb_index }
+                        if next_absorb_index < self.config.rate() =>
+                    {
+                        next_absorb_index
+                    }
+                    DuplexSpongeMode::Absorbing { .. } => {
+                        self.config.permute_var(&mut self.state)?;
+                        0
+                    }
+                    DuplexSpongeMode::Squeezing { .. } => 0,
+                };
+                self.state[self.config.capacity() + index] += f;
+                self.mode = DuplexSpongeMode::Absorbing {
+                    next_absorb_index: index + 1,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Squeeze `one` element, returning the first synthesis error.
+    pub fn try_squeeze(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        let index = match self.mode {
+            DuplexSpongeMode::Squeezing { next_squeeze_index }
+                if next_squeeze_index < self.config.rate() =>
+            {
+                next_squeeze_index
+            }
+            _ => {
+                self.config.permute_var(&mut self.state)?;
+                0
+            }
+        };
+        self.mode = DuplexSpongeMode::Squeezing {
+            next_squeeze_index: index + 1,
+        };
+        Ok(self.state[self.config.capacity() + index].clone())
+    }
+}
+
+/// In‑circuit `x^{1/alpha}`: the root is a fresh witness checked by `y^alpha = x`.
+pub(crate) fn inverse_sbox_var<F: PrimeField>(
+    x: &FpVar<F>,
+    alpha: u64,
+    alpha_inv: &[u64],
+) -> Result<FpVar<F>, SynthesisError> {
+    if let FpVar::Constant(c) = x {
+        return Ok(FpVar::Constant(c.pow(alpha_inv)));
+    }
+    let y = FpVar::new_witness(x.cs(), || x.value().map(|v| v.pow(alpha_inv)))?;
+    y.pow_by_constant([alpha])?.enforce_equal(x)?;
+    Ok(y)
+}
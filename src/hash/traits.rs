@@ -0,0 +1,147 @@
+//! Native + gadget hash traits.
+//!
+//! [`AlgebraicHash`] and [`AlgebraicHashGadget`] abstract over the
+//! sponges of this module so circuits such as
+//! [`PoseidonCircuit`](crate::circuit::PoseidonCircuit) can be
+//! instantiated with Poseidon, Poseidon2, Rescue‑Prime or Anemoi
+//! without touching the circuit code.
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_crypto_primitives::sponge::constraints::AbsorbGadget;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use super::sponge::{DuplexSponge, DuplexSpongeVar, SpongePermutation};
+use super::{PoseidonHash, PoseidonHashVar};
+
+/// Native sponge hash over `F`.
+pub trait AlgebraicHash<F: PrimeField>: Clone {
+    /// Construct a new sponge initialized with the default parameters.
+    fn new() -> Self;
+
+    /// Construct a new sponge whose capacity element is set to `domain`.
+    fn with_domain(domain: F) -> Self;
+
+    /// Absorb an iterator of field elements
+    fn absorb_many<I, A>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: Absorb;
+
+    /// Squeeze `one` field element from the sponge.
+    fn squeeze(&mut self) -> F;
+}
+
+/// In‑circuit counterpart of an [`AlgebraicHash`].
+pub trait AlgebraicHashGadget<F: PrimeField>: Sized {
+    /// Native hash producing the same outputs.
+    type Native: AlgebraicHash<F>;
+
+    /// Create a fresh sponge gadget inside the given constraint system.
+    fn new(cs: ConstraintSystemRef<F>) -> Self;
+
+    /// Create a sponge gadget whose capacity element is the constant `domain`.
+    fn with_domain(cs: ConstraintSystemRef<F>, domain: F) -> Self;
+
+    /// Absorb field gadgets.
+    fn absorb_many<I, A>(&mut self, iter: I) -> Result<(), SynthesisError>
+    where
+        I: IntoIterator<Item = A>,
+        A: AbsorbGadget<F>;
+
+    /// Squeeze `one` element.
+    fn squeeze(&mut self) -> Result<FpVar<F>, SynthesisError>;
+}
+
+impl<F: Absorb + PrimeField> AlgebraicHash<F> for PoseidonHash<F> {
+    fn new() -> Self {
+        PoseidonHash::new()
+    }
+
+    fn with_domain(domain: F) -> Self {
+        PoseidonHash::with_domain(domain)
+    }
+
+    fn absorb_many<I, A>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: Absorb,
+    {
+        PoseidonHash::absorb_many(self, iter)
+    }
+
+    fn squeeze(&mut self) -> F {
+        PoseidonHash::squeeze(self)
+    }
+}
+
+impl<F: Absorb + PrimeField> AlgebraicHashGadget<F> for PoseidonHashVar<F> {
+    type Native = PoseidonHash<F>;
+
+    fn new(cs: ConstraintSystemRef<F>) -> Self {
+        PoseidonHashVar::new(cs)
+    }
+
+    fn with_domain(cs: ConstraintSystemRef<F>, domain: F) -> Self {
+        PoseidonHashVar::with_domain(cs, domain)
+    }
+
+    fn absorb_many<I, A>(&mut self, iter: I) -> Result<(), SynthesisError>
+    where
+        I: IntoIterator<Item = A>,
+        A: AbsorbGadget<F>,
+    {
+        self.try_absorb_many(iter)
+    }
+
+    fn squeeze(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        self.try_squeeze()
+    }
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> AlgebraicHash<F> for DuplexSponge<F, P> {
+    fn new() -> Self {
+        DuplexSponge::new()
+    }
+
+    fn with_domain(domain: F) -> Self {
+        DuplexSponge::with_domain(domain)
+    }
+
+    fn absorb_many<I, A>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: Absorb,
+    {
+        DuplexSponge::absorb_many(self, iter)
+    }
+
+    fn squeeze(&mut self) -> F {
+        DuplexSponge::squeeze(self)
+    }
+}
+
+impl<F: PrimeField, P: SpongePermutation<F>> AlgebraicHashGadget<F> for DuplexSpongeVar<F, P> {
+    type Native = DuplexSponge<F, P>;
+
+    fn new(cs: ConstraintSystemRef<F>) -> Self {
+        DuplexSpongeVar::new(cs)
+    }
+
+    fn with_domain(cs: ConstraintSystemRef<F>, domain: F) -> Self {
+        DuplexSpongeVar::with_domain(cs, domain)
+    }
+
+    fn absorb_many<I, A>(&mut self, iter: I) -> Result<(), SynthesisError>
+    where
+        I: IntoIterator<Item = A>,
+        A: AbsorbGadget<F>,
+    {
+        self.try_absorb_many(iter)
+    }
+
+    fn squeeze(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        self.try_squeeze()
+    }
+}
@@ -4,6 +4,10 @@
 //! # Modules
 //! * [`hash`]    – Poseidon sponge configuration + helpers
 //! * [`circuit`] – Constraint system used in Groth16 benches
+//! * [`commitment`] – Hiding Poseidon commitments and opening gadget
+//! * [`gadgets`] – Range checks and comparisons over field variables
+//! * [`merkle`]  – Poseidon Merkle trees and membership gadgets
+//! * [`transcript`] – Poseidon Fiat–Shamir transcript (native + R1CS)
 //!
 //! The public surface of this crate is intentionally small:
 //! only items that are useful for down‑stream consumers are
@@ -11,6 +15,11 @@
 //! API easier to navigate.
 
 pub mod circuit;
+pub mod commitment;
+mod digest;
+pub mod gadgets;
 pub mod hash;
+pub mod merkle;
+pub mod transcript;
 
-pub use circuit::PoseidonCircuit;
+pub use circuit::{Poseidon2Circuit, PoseidonCircuit, ReportCircuit, VarLenPoseidonCircuit};
@@ -0,0 +1,431 @@
+//! Poseidon Merkle trees.
+//!
+//! [`MerkleTree`] is a fixed‑depth binary tree over `F` whose nodes are
+//! hashed with [`PoseidonHash`]; [`MerklePath`] is an authentication
+//! path and [`MerklePathVar`] verifies one in‑circuit with
+//! [`PoseidonHashVar`], typically against a public root and a private
+//! leaf and position (“this report comes from a registered reporter”).
+//!
+//! Leaves and internal nodes are hashed in separate domains,
+//!
+//! ```text
+//! leaf = H_leaf(value)      node = H_node(left, right)
+//! ```
+//!
+//! so a leaf can never be confused with an internal node. Both use the
+//! width‑3 (arity‑2) configuration of [`merkle_poseidon_config`], sized
+//! for 2‑to‑1 compression rather than the default width 5. Empty slots
+//! hold `0` at the leaf level and `H_node(z, z)` above it, which lets a
+//! tree of depth up to 64 store only its non‑empty prefix.
+//!
+//! [`IncrementalMerkleTree`] is the append‑only variant for registries
+//! that grow over time; its roots equal those of a [`MerkleTree`] over
+//! the same leaves. [`SparseMerkleTree`] maps field‑element keys to
+//! values and proves both membership and non‑membership. Both keep their
+//! state in a [`NodeStore`], either in memory or in a crash‑safe
+//! [`FileStore`].
+//! [`MerkleMountainRange`] is the tamper‑evident log of accepted reports,
+//! with inclusion and consistency proofs.
+
+use std::fmt;
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    R1CSVar,
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    select::CondSelectGadget,
+};
+use ark_relations::r1cs::{Namespace, SynthesisError};
+use std::borrow::Borrow;
+
+use crate::hash::params::smallest_alpha;
+use crate::hash::{PoseidonHash, PoseidonHashVar, PoseidonParamsBuilder, domain_tag};
+
+pub mod incremental;
+pub mod mmr;
+pub mod sparse;
+pub mod storage;
+
+pub use incremental::IncrementalMerkleTree;
+pub use mmr::{
+    ConsistencyProof, ConsistencyProofVar, MerkleMountainRange, MmrProof, MmrProofVar,
+};
+pub use sparse::{SparseMerkleProof, SparseMerkleProofVar, SparseMerkleTree};
+pub use storage::{FileStore, MemoryStore, NodeStore, StorageError};
+
+/// Largest supported depth (positions are `u64`).
+pub const MAX_DEPTH: usize = 64;
+
+/// Poseidon configuration of leaf and node hashes: arity 2, 128‑bit
+/// security and the smallest S‑box exponent that permutes `F`.
+///
+/// Built once per field; every hash after the first fetches it from the
+/// [`registry`](crate::hash::registry) without a round‑number search.
+pub fn merkle_poseidon_config<F: PrimeField>() -> Arc<PoseidonConfig<F>> {
+    PoseidonParamsBuilder::new()
+        .arity(2)
+        .alpha(smallest_alpha::<F>())
+        .build::<F>()
+        .expect("arity-2 Poseidon parameters are valid for every prime field")
+}
+
+/// Hash of a leaf value.
+pub fn hash_leaf<F: Absorb + PrimeField>(value: &F) -> F {
+    let config = merkle_poseidon_config::<F>();
+    let mut hash = PoseidonHash::with_config_and_domain(&config, domain_tag(b"merkle-leaf"));
+    hash.absorb_many([value]);
+    hash.squeeze()
+}
+
+/// Hash of two child nodes.
+pub fn hash_node<F: Absorb + PrimeField>(left: &F, right: &F) -> F {
+    let config = merkle_poseidon_config::<F>();
+    let mut hash = PoseidonHash::with_config_and_domain(&config, domain_tag(b"merkle-node"));
+    hash.absorb_many([left, right]);
+    hash.squeeze()
+}
+
+/// In‑circuit [`hash_leaf`].
+pub fn hash_leaf_var<F: Absorb + PrimeField>(value: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+    let config = merkle_poseidon_config::<F>();
+    let mut hash =
+        PoseidonHashVar::with_config_and_domain(value.cs(), &config, domain_tag(b"merkle-leaf"));
+    hash.try_absorb_many([value])?;
+    hash.try_squeeze()
+}
+
+/// In‑circuit [`hash_node`].
+pub fn hash_node_var<F: Absorb + PrimeField>(
+    left: &FpVar<F>,
+    right: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let cs = left.cs().or(right.cs());
+    let config = merkle_poseidon_config::<F>();
+    let mut hash = PoseidonHashVar::with_config_and_domain(cs, &config, domain_tag(b"merkle-node"));
+    hash.try_absorb_many([left, right])?;
+    hash.try_squeeze()
+}
+
+/// Roots of empty subtrees: `zeros[h]` is the empty node at height `h`.
+pub fn empty_nodes<F: Absorb + PrimeField>(depth: usize) -> Vec<F> {
+    let mut zeros = vec![F::zero()];
+    for h in 0..depth {
+        zeros.push(hash_node(&zeros[h], &zeros[h]));
+    }
+    zeros
+}
+
+/// Errors returned by Merkle tree operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MerkleError {
+    /// The depth exceeds [`MAX_DEPTH`].
+    UnsupportedDepth(usize),
+    /// More leaves than the tree has slots.
+    TooManyLeaves { capacity: u64, given: u64 },
+    /// The position is outside the tree (or past its last leaf).
+    IndexOutOfRange(u64),
+    /// Every slot of an append‑only tree is taken.
+    TreeFull,
+    /// A root history must keep at least one root.
+    EmptyHistory,
+    /// The node store failed or holds an inconsistent tree.
+    Storage(StorageError),
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedDepth(depth) => write!(f, "depth {depth} exceeds {MAX_DEPTH}"),
+            Self::TooManyLeaves { capacity, given } => {
+                write!(f, "{given} leaves do not fit in {capacity} slots")
+            }
+            Self::IndexOutOfRange(index) => write!(f, "leaf index {index} out of range"),
+            Self::TreeFull => write!(f, "Merkle tree is full"),
+            Self::EmptyHistory => write!(f, "root history must keep at least one root"),
+            Self::Storage(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MerkleError {}
+
+impl From<StorageError> for MerkleError {
+    fn from(e: StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+/// Number of slots of a tree of the given depth (saturating at `u64::MAX`).
+pub(crate) fn capacity(depth: usize) -> u64 {
+    1u64.checked_shl(depth as u32).unwrap_or(u64::MAX)
+}
+
+/// Authentication path from a leaf to the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath<F: PrimeField> {
+    /// Position of the leaf; bit `h` is set when the node at height `h`
+    /// is a right child.
+    pub index: u64,
+    /// Sibling nodes, from the leaf level upwards.
+    pub siblings: Vec<F>,
+}
+
+impl<F: Absorb + PrimeField> MerklePath<F> {
+    /// Root obtained by hashing `leaf` (a leaf value) up the path.
+    pub fn compute_root(&self, leaf: &F) -> F {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(hash_leaf(leaf), |node, (h, sibling)| {
+                if (self.index >> h) & 1 == 1 {
+                    hash_node(sibling, &node)
+                } else {
+                    hash_node(&node, sibling)
+                }
+            })
+    }
+
+    /// Whether `leaf` sits at `self.index` under `root`.
+    pub fn verify(&self, root: &F, leaf: &F) -> bool {
+        self.compute_root(leaf) == *root
+    }
+}
+
+/// Fixed‑depth binary Poseidon Merkle tree.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<F: PrimeField> {
+    depth: usize,
+    /// `layers[h]` holds the non‑empty prefix of height `h`.
+    layers: Vec<Vec<F>>,
+    zeros: Vec<F>,
+}
+
+impl<F: Absorb + PrimeField> MerkleTree<F> {
+    /// Build a tree of `depth` whose first slots hold `leaves` (values,
+    /// hashed with [`hash_leaf`]); the remaining slots are empty.
+    pub fn new(depth: usize, leaves: &[F]) -> Result<Self, MerkleError> {
+        if depth > MAX_DEPTH {
+            return Err(MerkleError::UnsupportedDepth(depth));
+        }
+        let given = leaves.len() as u64;
+        if given > capacity(depth) {
+            return Err(MerkleError::TooManyLeaves {
+                capacity: capacity(depth),
+                given,
+            });
+        }
+
+        let zeros = empty_nodes(depth);
+        let mut layers = vec![leaves.iter().map(hash_leaf).collect::<Vec<F>>()];
+        for h in 0..depth {
+            let next = layers[h]
+                .chunks(2)
+                .map(|pair| hash_node(&pair[0], pair.get(1).unwrap_or(&zeros[h])))
+                .collect();
+            layers.push(next);
+        }
+        Ok(Self {
+            depth,
+            layers,
+            zeros,
+        })
+    }
+
+    /// Depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Number of (non‑empty) leaves.
+    pub fn num_leaves(&self) -> u64 {
+        self.layers[0].len() as u64
+    }
+
+    /// Current root.
+    pub fn root(&self) -> F {
+        self.layers[self.depth]
+            .first()
+            .copied()
+            .unwrap_or(self.zeros[self.depth])
+    }
+
+    /// Authentication path of the leaf at `index`.
+    pub fn path(&self, index: u64) -> Result<MerklePath<F>, MerkleError> {
+        if index >= self.num_leaves() {
+            return Err(MerkleError::IndexOutOfRange(index));
+        }
+        let siblings = (0..self.depth)
+            .map(|h| {
+                let sibling = ((index >> h) ^ 1) as usize;
+                self.layers[h]
+                    .get(sibling)
+                    .copied()
+                    .unwrap_or(self.zeros[h])
+            })
+            .collect();
+        Ok(MerklePath { index, siblings })
+    }
+}
+
+/// In‑circuit [`MerklePath`]: position bits and siblings are allocated
+/// with the same mode (usually witnesses, keeping the position private).
+#[derive(Clone)]
+pub struct MerklePathVar<F: PrimeField> {
+    /// Position bits, little‑endian (one per level).
+    pub index_bits: Vec<Boolean<F>>,
+    /// Sibling nodes, from the leaf level upwards.
+    pub siblings: Vec<FpVar<F>>,
+}
+
+impl<F: Absorb + PrimeField> AllocVar<MerklePath<F>, F> for MerklePathVar<F> {
+    fn new_variable<T: Borrow<MerklePath<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let path = f()?;
+        let path = path.borrow();
+        let index_bits = (0..path.siblings.len())
+            .map(|h| Boolean::new_variable(cs.clone(), || Ok((path.index >> h) & 1 == 1), mode))
+            .collect::<Result<_, _>>()?;
+        let siblings = Vec::new_variable(cs, || Ok(path.siblings.clone()), mode)?;
+        Ok(Self {
+            index_bits,
+            siblings,
+        })
+    }
+}
+
+impl<F: Absorb + PrimeField> MerklePathVar<F> {
+    /// Root obtained by hashing `leaf` (a leaf value) up the path.
+    pub fn compute_root(&self, leaf: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        let mut node = hash_leaf_var(leaf)?;
+        for (is_right, sibling) in self.index_bits.iter().zip(&self.siblings) {
+            let left = FpVar::conditionally_select(is_right, sibling, &node)?;
+            let right = FpVar::conditionally_select(is_right, &node, sibling)?;
+            node = hash_node_var(&left, &right)?;
+        }
+        Ok(node)
+    }
+
+    /// Whether `leaf` sits at the path’s position under `root`.
+    pub fn verify_membership(
+        &self,
+        root: &FpVar<F>,
+        leaf: &FpVar<F>,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        self.compute_root(leaf)?.is_eq(root)
+    }
+
+    /// Enforce that `leaf` sits at the path’s position under `root`.
+    pub fn enforce_membership(
+        &self,
+        root: &FpVar<F>,
+        leaf: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        self.compute_root(leaf)?.enforce_equal(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn nodes_are_hashed_with_width_three() {
+        let config = merkle_poseidon_config::<Fr>();
+        assert_eq!((config.rate, config.capacity), (2, 1));
+        assert!(Arc::ptr_eq(&config, &merkle_poseidon_config::<Fr>()));
+
+        let cs = ConstraintSystem::new_ref();
+        let (a, b) = (Fr::from(1u64), Fr::from(2u64));
+        let a_var = FpVar::new_witness(cs.clone(), || Ok(a)).unwrap();
+        let b_var = FpVar::new_witness(cs.clone(), || Ok(b)).unwrap();
+        let node = hash_node_var(&a_var, &b_var).unwrap();
+        assert_eq!(node.value().unwrap(), hash_node(&a, &b));
+        let narrow = cs.num_constraints();
+
+        // the same compression with the default width‑5 sponge
+        let mut hash = PoseidonHashVar::with_domain(cs.clone(), domain_tag(b"merkle-node"));
+        hash.try_absorb_many([&a_var, &b_var]).unwrap();
+        let _ = hash.try_squeeze().unwrap();
+        let wide = cs.num_constraints() - narrow;
+        assert!(narrow < wide, "{narrow} >= {wide}");
+    }
+
+    #[test]
+    fn paths_verify_natively_and_in_circuit() {
+        let leaves: Vec<Fr> = (100u64..110).map(Fr::from).collect();
+        let tree = MerkleTree::new(4, &leaves).unwrap();
+        let root = tree.root();
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path = tree.path(i as u64).unwrap();
+            assert!(path.verify(&root, leaf));
+            assert!(!path.verify(&root, &Fr::from(7u64)));
+        }
+        assert_eq!(tree.path(10), Err(MerkleError::IndexOutOfRange(10)));
+
+        let path = tree.path(5).unwrap();
+        let cs = ConstraintSystem::new_ref();
+        let root_var = FpVar::new_input(cs.clone(), || Ok(root)).unwrap();
+        let leaf_var = FpVar::new_witness(cs.clone(), || Ok(leaves[5])).unwrap();
+        let path_var = MerklePathVar::new_witness(cs.clone(), || Ok(&path)).unwrap();
+        path_var.enforce_membership(&root_var, &leaf_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_instance_variables(), 2);
+
+        // a non‑member leaf fails
+        let cs = ConstraintSystem::new_ref();
+        let root_var = FpVar::new_input(cs.clone(), || Ok(root)).unwrap();
+        let leaf_var = FpVar::new_witness(cs.clone(), || Ok(leaves[4])).unwrap();
+        let path_var = MerklePathVar::new_witness(cs.clone(), || Ok(&path)).unwrap();
+        assert!(
+            !path_var
+                .verify_membership(&root_var, &leaf_var)
+                .unwrap()
+                .value()
+                .unwrap()
+        );
+        path_var.enforce_membership(&root_var, &leaf_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn empty_slots_and_limits() {
+        let zeros = empty_nodes::<Fr>(3);
+        assert_eq!(MerkleTree::<Fr>::new(3, &[]).unwrap().root(), zeros[3]);
+
+        let one = MerkleTree::new(3, &[Fr::from(1u64)]).unwrap();
+        let full = MerkleTree::new(3, &[Fr::from(1u64), Fr::from(0u64)]).unwrap();
+        // an explicit zero leaf is hashed, an empty slot is not
+        assert_ne!(one.root(), full.root());
+
+        assert_eq!(
+            MerkleTree::new(1, &[Fr::from(1u64); 3]).unwrap_err(),
+            MerkleError::TooManyLeaves {
+                capacity: 2,
+                given: 3
+            }
+        );
+        assert_eq!(
+            MerkleTree::<Fr>::new(65, &[]).unwrap_err(),
+            MerkleError::UnsupportedDepth(65)
+        );
+        assert!(
+            MerkleTree::<Fr>::new(64, &[Fr::from(1u64)])
+                .unwrap()
+                .path(0)
+                .is_ok()
+        );
+    }
+}
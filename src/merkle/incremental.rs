@@ -0,0 +1,283 @@
+//! Append‑only Poseidon Merkle tree with a root history.
+//!
+//! Follows the “filled subtrees” construction of Tornado Cash: the tree
+//! keeps, per level, the last left child whose right sibling is still
+//! empty, so an insert hashes exactly `depth` nodes and no other inner
+//! node is needed. Paths for proving are served by a
+//! [`MerkleTree`](super::MerkleTree) over the same leaves, whose root is
+//! identical.
+//!
+//! Every insert pushes the new root into a ring buffer of the last `K`
+//! roots, so a verifier can accept a proof made against a slightly
+//! stale root while new reporters keep registering.
+//!
+//! The leaf values, the filled subtrees and the root ring are written to
+//! a [`NodeStore`]: leaf `i` at `(0, [i])`, and the other two at heights
+//! above any tree node, `filled_subtrees[h]` at `(FILLED_SUBTREES, [h])`
+//! and ring slot `k` at `(ROOTS, [k])`. Reopening a store recomputes the
+//! latest root from the last leaf and the filled subtrees and checks it
+//! against the committed root.
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+
+use super::storage::{MemoryStore, NodeStore, StorageError};
+use super::{MAX_DEPTH, MerkleError, MerklePath, capacity, empty_nodes, hash_leaf, hash_node};
+
+/// Store height of the root ring.
+const ROOTS: usize = MAX_DEPTH + 1;
+/// Store height of the filled subtrees.
+const FILLED_SUBTREES: usize = MAX_DEPTH + 2;
+
+/// Append‑only Merkle tree remembering its last `K` roots, its state
+/// kept in a [`NodeStore`].
+#[derive(Clone, Debug)]
+pub struct IncrementalMerkleTree<F: PrimeField, S: NodeStore<F> = MemoryStore<F>> {
+    store: S,
+    depth: usize,
+    next_index: u64,
+    /// `filled_subtrees[h]`: last left node at height `h`.
+    filled_subtrees: Vec<F>,
+    zeros: Vec<F>,
+    /// Ring buffer of recent roots; `roots[current]` is the latest.
+    roots: Vec<F>,
+    current: usize,
+    history: usize,
+}
+
+impl<F: Absorb + PrimeField> IncrementalMerkleTree<F> {
+    /// An empty in‑memory tree of `depth` remembering its last `history`
+    /// roots.
+    pub fn new(depth: usize, history: usize) -> Result<Self, MerkleError> {
+        Self::open(MemoryStore::new(), depth, history)
+    }
+}
+
+impl<F: Absorb + PrimeField, S: NodeStore<F>> IncrementalMerkleTree<F, S> {
+    /// Open the tree of `depth` and `history` held by `store`, starting
+    /// an empty one if the store is empty.
+    ///
+    /// The leaves must sit at positions `0..n`, the ring must hold
+    /// `min(n + 1, history)` roots, and the latest of them must follow
+    /// from the last leaf and the filled subtrees and match the last
+    /// committed root; otherwise [`StorageError::RootMismatch`] is
+    /// returned.
+    pub fn open(mut store: S, depth: usize, history: usize) -> Result<Self, MerkleError> {
+        if depth > MAX_DEPTH {
+            return Err(MerkleError::UnsupportedDepth(depth));
+        }
+        if history == 0 {
+            return Err(MerkleError::EmptyHistory);
+        }
+        let zeros = empty_nodes(depth);
+        let leaves = store.nodes(0);
+        let next_index = leaves.len() as u64;
+        if next_index > capacity(depth) {
+            return Err(MerkleError::TooManyLeaves {
+                capacity: capacity(depth),
+                given: next_index,
+            });
+        }
+        if next_index == 0 && store.count(ROOTS) == 0 {
+            store.put(ROOTS, &[0], Some(zeros[depth]));
+        }
+
+        let in_order = leaves
+            .iter()
+            .zip(0u64..)
+            .all(|((position, _), i)| position[..] == [i]);
+        let kept = next_index.saturating_add(1).min(history as u64);
+        let roots = (0..kept)
+            .map(|slot| store.get(ROOTS, &[slot]))
+            .collect::<Option<Vec<_>>>()
+            .filter(|roots| in_order && roots.len() == store.count(ROOTS))
+            .ok_or(StorageError::RootMismatch)?;
+
+        let filled_subtrees = (0..depth)
+            .map(|h| store.get(FILLED_SUBTREES, &[h as u64]).unwrap_or(zeros[h]))
+            .collect();
+        let tree = Self {
+            store,
+            depth,
+            next_index,
+            filled_subtrees,
+            zeros,
+            roots,
+            current: (next_index % history as u64) as usize,
+            history,
+        };
+
+        let latest = match leaves.last() {
+            Some((_, leaf)) => tree.recompute_root(next_index - 1, leaf),
+            None => Some(tree.zeros[depth]),
+        };
+        let root = tree.root();
+        if latest != Some(root) || tree.store.committed_root().is_some_and(|r| r != root) {
+            return Err(StorageError::RootMismatch.into());
+        }
+        Ok(tree)
+    }
+
+    /// Root after inserting `leaf` at `index`, given the filled subtrees
+    /// that insert left behind; `None` if they do not contain its own
+    /// left nodes.
+    fn recompute_root(&self, index: u64, leaf: &F) -> Option<F> {
+        let mut node = hash_leaf(leaf);
+        for h in 0..self.depth {
+            node = if (index >> h) & 1 == 0 {
+                if self.filled_subtrees[h] != node {
+                    return None;
+                }
+                hash_node(&node, &self.zeros[h])
+            } else {
+                hash_node(&self.filled_subtrees[h], &node)
+            };
+        }
+        Some(node)
+    }
+
+    /// Persist the current state and return the committed root.
+    pub fn commit(&mut self) -> Result<F, StorageError> {
+        let root = self.root();
+        self.store.commit(root)?;
+        Ok(root)
+    }
+
+    /// The underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Number of inserted leaves.
+    pub fn num_leaves(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Number of roots kept in the history.
+    pub fn history(&self) -> usize {
+        self.history
+    }
+
+    /// Append a leaf value and return its position.
+    pub fn insert(&mut self, value: &F) -> Result<u64, MerkleError> {
+        let index = self.next_index;
+        if index >= capacity(self.depth) {
+            return Err(MerkleError::TreeFull);
+        }
+
+        self.store.put(0, &[index], Some(*value));
+        let mut node = hash_leaf(value);
+        for h in 0..self.depth {
+            if (index >> h) & 1 == 0 {
+                self.filled_subtrees[h] = node;
+                self.store.put(FILLED_SUBTREES, &[h as u64], Some(node));
+                node = hash_node(&node, &self.zeros[h]);
+            } else {
+                node = hash_node(&self.filled_subtrees[h], &node);
+            }
+        }
+
+        if self.roots.len() < self.history {
+            self.roots.push(node);
+            self.current = self.roots.len() - 1;
+        } else {
+            self.current = (self.current + 1) % self.roots.len();
+            self.roots[self.current] = node;
+        }
+        self.store.put(ROOTS, &[self.current as u64], Some(node));
+        self.next_index += 1;
+        Ok(index)
+    }
+
+    /// Latest root.
+    pub fn root(&self) -> F {
+        self.roots[self.current]
+    }
+
+    /// Whether `root` is one of the last `K` roots.
+    pub fn is_known_root(&self, root: &F) -> bool {
+        self.roots.contains(root)
+    }
+
+    /// Recent roots, latest first.
+    pub fn known_roots(&self) -> impl Iterator<Item = &F> {
+        let (latest, oldest) = self.roots.split_at(self.current + 1);
+        latest.iter().rev().chain(oldest.iter().rev())
+    }
+
+    /// Whether `leaf` sits at `path.index` under any known root.
+    pub fn verify(&self, path: &MerklePath<F>, leaf: &F) -> bool {
+        path.siblings.len() == self.depth && self.is_known_root(&path.compute_root(leaf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::{MerklePathVar, MerkleTree};
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn roots_match_full_tree_and_circuit() {
+        let mut tree = IncrementalMerkleTree::<Fr>::new(5, 4).unwrap();
+        assert_eq!(tree.root(), MerkleTree::<Fr>::new(5, &[]).unwrap().root());
+
+        let mut leaves = Vec::new();
+        for i in 0..9u64 {
+            let leaf = Fr::from(1000 + i);
+            assert_eq!(tree.insert(&leaf).unwrap(), i);
+            leaves.push(leaf);
+            assert_eq!(tree.root(), MerkleTree::new(5, &leaves).unwrap().root());
+        }
+
+        let full = MerkleTree::new(5, &leaves).unwrap();
+        let path = full.path(3).unwrap();
+        assert!(tree.verify(&path, &leaves[3]));
+
+        let cs = ConstraintSystem::new_ref();
+        let root = FpVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+        let leaf = FpVar::new_witness(cs.clone(), || Ok(leaves[3])).unwrap();
+        let path = MerklePathVar::new_witness(cs.clone(), || Ok(&path)).unwrap();
+        path.enforce_membership(&root, &leaf).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn history_accepts_only_recent_roots() {
+        let mut tree = IncrementalMerkleTree::<Fr>::new(3, 3).unwrap();
+        let mut leaves = Vec::new();
+        let mut paths = Vec::new();
+        for i in 0..4u64 {
+            leaves.push(Fr::from(i + 1));
+            tree.insert(&leaves[i as usize]).unwrap();
+            paths.push(MerkleTree::new(3, &leaves).unwrap().path(0).unwrap());
+        }
+
+        // proofs made against the last three roots verify, older ones do not
+        assert!(!tree.verify(&paths[0], &leaves[0]));
+        assert!(paths[1..].iter().all(|p| tree.verify(p, &leaves[0])));
+        let expected: Vec<Fr> = paths
+            .iter()
+            .rev()
+            .take(3)
+            .map(|p| p.compute_root(&leaves[0]))
+            .collect();
+        assert_eq!(tree.known_roots().copied().collect::<Vec<_>>(), expected);
+
+        for i in 4..8u64 {
+            tree.insert(&Fr::from(i + 1)).unwrap();
+        }
+        assert_eq!(tree.insert(&Fr::from(9u64)), Err(MerkleError::TreeFull));
+        assert_eq!(
+            IncrementalMerkleTree::<Fr>::new(3, 0).unwrap_err(),
+            MerkleError::EmptyHistory
+        );
+    }
+}
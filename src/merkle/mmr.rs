@@ -0,0 +1,549 @@
+//! Poseidon Merkle Mountain Range: an append‑only transparency log.
+//!
+//! An MMR of `n` leaves is a list of perfect binary trees (its *peaks*),
+//! one per set bit of `n`, largest first; appending a leaf merges equal
+//! peaks. Leaves and nodes are hashed like the other trees of this
+//! module ([`hash_leaf`], [`hash_node`]) and the peaks are *bagged* into
+//! the root together with the size,
+//!
+//! ```text
+//! root = H_mmr(n, peak_0, …, peak_k)
+//! ```
+//!
+//! so a root commits to the exact length of the log.
+//!
+//! * [`MmrProof`] shows that a leaf sits at a position of the log;
+//! * [`ConsistencyProof`] shows that the log of size `m` is a prefix of
+//!   the log of size `n ≥ m`: every old peak is hashed up to the new peak
+//!   containing it, so an operator who rewrote history cannot produce one.
+//!
+//! Sizes and positions are public: the gadgets take them as circuit
+//! constants and allocate only the hashes.
+
+use std::borrow::Borrow;
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    R1CSVar,
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{FieldVar, fp::FpVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+
+use super::{MerkleError, hash_leaf, hash_leaf_var, hash_node, hash_node_var};
+use crate::hash::{PoseidonHash, PoseidonHashVar, domain_tag};
+
+/// Root of an MMR of `size` leaves with the given peaks (left to right).
+pub fn bag_peaks<F: Absorb + PrimeField>(size: u64, peaks: &[F]) -> F {
+    let mut hash = PoseidonHash::with_domain(domain_tag(b"mmr-root"));
+    hash.absorb_many([F::from(size)]);
+    hash.absorb_many(peaks);
+    hash.squeeze()
+}
+
+/// In‑circuit [`bag_peaks`]; `size` is a constant.
+pub fn bag_peaks_var<F: Absorb + PrimeField>(
+    size: u64,
+    peaks: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let cs = peaks
+        .iter()
+        .fold(ConstraintSystemRef::None, |cs, peak| cs.or(peak.cs()));
+    let mut hash = PoseidonHashVar::with_domain(cs, domain_tag(b"mmr-root"));
+    hash.try_absorb_many([FpVar::constant(F::from(size))])?;
+    hash.try_absorb_many(peaks)?;
+    hash.try_squeeze()
+}
+
+/// Peaks of an MMR of `size` leaves, left to right, as
+/// `(height, index among the nodes of that height)`.
+fn peak_positions(size: u64) -> Vec<(usize, u64)> {
+    (0..64)
+        .rev()
+        .filter(|h| (size >> h) & 1 == 1)
+        .map(|h| (h, (size >> h) - 1))
+        .collect()
+}
+
+/// Index of the peak of `peaks` containing the node `(height, index)`.
+fn containing_peak(peaks: &[(usize, u64)], height: usize, index: u64) -> Option<usize> {
+    peaks
+        .iter()
+        .position(|&(h, i)| h >= height && index >> (h - height) == i)
+}
+
+/// Hash `node`, sitting at `index` among its level, up `siblings`.
+fn climb<F: Absorb + PrimeField>(mut node: F, index: u64, siblings: &[F]) -> F {
+    for (t, sibling) in siblings.iter().enumerate() {
+        node = if (index >> t) & 1 == 1 {
+            hash_node(sibling, &node)
+        } else {
+            hash_node(&node, sibling)
+        };
+    }
+    node
+}
+
+/// In‑circuit [`climb`]; the position is a constant.
+fn climb_var<F: Absorb + PrimeField>(
+    mut node: FpVar<F>,
+    index: u64,
+    siblings: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    for (t, sibling) in siblings.iter().enumerate() {
+        node = if (index >> t) & 1 == 1 {
+            hash_node_var(sibling, &node)?
+        } else {
+            hash_node_var(&node, sibling)?
+        };
+    }
+    Ok(node)
+}
+
+/// Append‑only Poseidon Merkle Mountain Range.
+#[derive(Clone, Debug)]
+pub struct MerkleMountainRange<F: PrimeField> {
+    /// `levels[h]` holds every complete node of height `h`, in order.
+    levels: Vec<Vec<F>>,
+}
+
+impl<F: Absorb + PrimeField> Default for MerkleMountainRange<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Absorb + PrimeField> MerkleMountainRange<F> {
+    /// An empty log.
+    pub fn new() -> Self {
+        Self {
+            levels: vec![Vec::new()],
+        }
+    }
+
+    /// Number of appended leaves.
+    pub fn size(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    /// Append a leaf value and return its position.
+    pub fn append(&mut self, value: &F) -> u64 {
+        let index = self.size();
+        self.levels[0].push(hash_leaf(value));
+        let mut h = 0;
+        while self.levels[h].len().is_multiple_of(2) {
+            let level = &self.levels[h];
+            let node = hash_node(&level[level.len() - 2], &level[level.len() - 1]);
+            if self.levels.len() == h + 1 {
+                self.levels.push(Vec::new());
+            }
+            self.levels[h + 1].push(node);
+            h += 1;
+        }
+        index
+    }
+
+    /// Current root.
+    pub fn root(&self) -> F {
+        bag_peaks(self.size(), &self.peaks(self.size()))
+    }
+
+    /// Root the log had when it held `size` leaves.
+    pub fn root_at(&self, size: u64) -> Result<F, MerkleError> {
+        if size > self.size() {
+            return Err(MerkleError::IndexOutOfRange(size));
+        }
+        Ok(bag_peaks(size, &self.peaks(size)))
+    }
+
+    fn node(&self, height: usize, index: u64) -> F {
+        self.levels[height][index as usize]
+    }
+
+    fn peaks(&self, size: u64) -> Vec<F> {
+        peak_positions(size)
+            .into_iter()
+            .map(|(h, i)| self.node(h, i))
+            .collect()
+    }
+
+    /// Siblings of `(height, index)` up to `top`.
+    fn siblings(&self, height: usize, index: u64, top: usize) -> Vec<F> {
+        (height..top)
+            .map(|h| self.node(h, (index >> (h - height)) ^ 1))
+            .collect()
+    }
+
+    /// Inclusion proof of the leaf at `index` in the current log.
+    pub fn prove(&self, index: u64) -> Result<MmrProof<F>, MerkleError> {
+        let size = self.size();
+        let layout = peak_positions(size);
+        let k = containing_peak(&layout, 0, index).ok_or(MerkleError::IndexOutOfRange(index))?;
+        let mut peaks = self.peaks(size);
+        peaks.remove(k);
+        Ok(MmrProof {
+            index,
+            size,
+            siblings: self.siblings(0, index, layout[k].0),
+            peaks,
+        })
+    }
+
+    /// Proof that the log of `old_size` leaves is a prefix of the log of
+    /// `new_size` leaves.
+    pub fn prove_consistency(
+        &self,
+        old_size: u64,
+        new_size: u64,
+    ) -> Result<ConsistencyProof<F>, MerkleError> {
+        if new_size > self.size() {
+            return Err(MerkleError::IndexOutOfRange(new_size));
+        }
+        if old_size > new_size {
+            return Err(MerkleError::IndexOutOfRange(old_size));
+        }
+        let new_layout = peak_positions(new_size);
+        let paths = peak_positions(old_size)
+            .into_iter()
+            .map(|(h, i)| {
+                let k = containing_peak(&new_layout, h, i).expect("old peaks lie in new peaks");
+                self.siblings(h, i, new_layout[k].0)
+            })
+            .collect();
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            old_peaks: self.peaks(old_size),
+            paths,
+            new_peaks: self.peaks(new_size),
+        })
+    }
+}
+
+/// Inclusion proof of one leaf of a [`MerkleMountainRange`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MmrProof<F: PrimeField> {
+    /// Position of the leaf.
+    pub index: u64,
+    /// Size of the log the proof was made against.
+    pub size: u64,
+    /// Siblings from the leaf up to its peak.
+    pub siblings: Vec<F>,
+    /// The other peaks, left to right.
+    pub peaks: Vec<F>,
+}
+
+impl<F: Absorb + PrimeField> MmrProof<F> {
+    /// Root obtained for `leaf` (a leaf value), or `None` if the proof is
+    /// malformed.
+    pub fn compute_root(&self, leaf: &F) -> Option<F> {
+        let layout = peak_positions(self.size);
+        let k = containing_peak(&layout, 0, self.index)?;
+        if self.siblings.len() != layout[k].0 || self.peaks.len() + 1 != layout.len() {
+            return None;
+        }
+        let mut peaks = self.peaks.clone();
+        peaks.insert(k, climb(hash_leaf(leaf), self.index, &self.siblings));
+        Some(bag_peaks(self.size, &peaks))
+    }
+
+    /// Whether `leaf` sits at `self.index` of the log with `root`.
+    pub fn verify(&self, root: &F, leaf: &F) -> bool {
+        self.compute_root(leaf) == Some(*root)
+    }
+}
+
+/// Proof that one log size extends another (see the module docs).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsistencyProof<F: PrimeField> {
+    /// Size of the older log.
+    pub old_size: u64,
+    /// Size of the newer log.
+    pub new_size: u64,
+    /// Peaks of the older log.
+    pub old_peaks: Vec<F>,
+    /// For each old peak, the siblings up to the new peak containing it.
+    pub paths: Vec<Vec<F>>,
+    /// Peaks of the newer log.
+    pub new_peaks: Vec<F>,
+}
+
+impl<F: Absorb + PrimeField> ConsistencyProof<F> {
+    /// Whether the log with `new_root` extends the log with `old_root`.
+    pub fn verify(&self, old_root: &F, new_root: &F) -> bool {
+        let old_layout = peak_positions(self.old_size);
+        let new_layout = peak_positions(self.new_size);
+        if self.old_size > self.new_size
+            || self.old_peaks.len() != old_layout.len()
+            || self.paths.len() != old_layout.len()
+            || self.new_peaks.len() != new_layout.len()
+            || bag_peaks(self.old_size, &self.old_peaks) != *old_root
+            || bag_peaks(self.new_size, &self.new_peaks) != *new_root
+        {
+            return false;
+        }
+        old_layout
+            .iter()
+            .zip(self.old_peaks.iter().zip(&self.paths))
+            .all(|(&(h, i), (peak, path))| {
+                let k = containing_peak(&new_layout, h, i).expect("old peaks lie in new peaks");
+                path.len() == new_layout[k].0 - h && climb(*peak, i, path) == self.new_peaks[k]
+            })
+    }
+}
+
+/// In‑circuit [`MmrProof`]; position and size are constants.
+#[derive(Clone)]
+pub struct MmrProofVar<F: PrimeField> {
+    /// Position of the leaf.
+    pub index: u64,
+    /// Size of the log the proof was made against.
+    pub size: u64,
+    /// Siblings from the leaf up to its peak.
+    pub siblings: Vec<FpVar<F>>,
+    /// The other peaks, left to right.
+    pub peaks: Vec<FpVar<F>>,
+}
+
+impl<F: Absorb + PrimeField> AllocVar<MmrProof<F>, F> for MmrProofVar<F> {
+    fn new_variable<T: Borrow<MmrProof<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let proof = f()?;
+        let proof = proof.borrow();
+        Ok(Self {
+            index: proof.index,
+            size: proof.size,
+            siblings: Vec::new_variable(cs.clone(), || Ok(proof.siblings.clone()), mode)?,
+            peaks: Vec::new_variable(cs, || Ok(proof.peaks.clone()), mode)?,
+        })
+    }
+}
+
+impl<F: Absorb + PrimeField> MmrProofVar<F> {
+    /// Root obtained for `leaf` (a leaf value).
+    pub fn compute_root(&self, leaf: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        let layout = peak_positions(self.size);
+        let k = containing_peak(&layout, 0, self.index).ok_or(SynthesisError::Unsatisfiable)?;
+        if self.siblings.len() != layout[k].0 || self.peaks.len() + 1 != layout.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut peaks = self.peaks.clone();
+        peaks.insert(
+            k,
+            climb_var(hash_leaf_var(leaf)?, self.index, &self.siblings)?,
+        );
+        bag_peaks_var(self.size, &peaks)
+    }
+
+    /// Whether `leaf` sits at `self.index` of the log with `root`.
+    pub fn verify_inclusion(
+        &self,
+        root: &FpVar<F>,
+        leaf: &FpVar<F>,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        self.compute_root(leaf)?.is_eq(root)
+    }
+
+    /// Enforce that `leaf` sits at `self.index` of the log with `root`.
+    pub fn enforce_inclusion(
+        &self,
+        root: &FpVar<F>,
+        leaf: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        self.compute_root(leaf)?.enforce_equal(root)
+    }
+}
+
+type Equalities<F> = Vec<(FpVar<F>, FpVar<F>)>;
+
+/// In‑circuit [`ConsistencyProof`]; sizes are constants.
+#[derive(Clone)]
+pub struct ConsistencyProofVar<F: PrimeField> {
+    /// Size of the older log.
+    pub old_size: u64,
+    /// Size of the newer log.
+    pub new_size: u64,
+    /// Peaks of the older log.
+    pub old_peaks: Vec<FpVar<F>>,
+    /// For each old peak, the siblings up to the new peak containing it.
+    pub paths: Vec<Vec<FpVar<F>>>,
+    /// Peaks of the newer log.
+    pub new_peaks: Vec<FpVar<F>>,
+}
+
+impl<F: Absorb + PrimeField> AllocVar<ConsistencyProof<F>, F> for ConsistencyProofVar<F> {
+    fn new_variable<T: Borrow<ConsistencyProof<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let proof = f()?;
+        let proof = proof.borrow();
+        let paths = proof
+            .paths
+            .iter()
+            .map(|path| Vec::new_variable(cs.clone(), || Ok(path.clone()), mode))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            old_size: proof.old_size,
+            new_size: proof.new_size,
+            old_peaks: Vec::new_variable(cs.clone(), || Ok(proof.old_peaks.clone()), mode)?,
+            paths,
+            new_peaks: Vec::new_variable(cs, || Ok(proof.new_peaks.clone()), mode)?,
+        })
+    }
+}
+
+impl<F: Absorb + PrimeField> ConsistencyProofVar<F> {
+    /// Pairs of values that must be equal for the proof to hold.
+    fn equalities(
+        &self,
+        old_root: &FpVar<F>,
+        new_root: &FpVar<F>,
+    ) -> Result<Equalities<F>, SynthesisError> {
+        let old_layout = peak_positions(self.old_size);
+        let new_layout = peak_positions(self.new_size);
+        if self.old_size > self.new_size
+            || self.old_peaks.len() != old_layout.len()
+            || self.paths.len() != old_layout.len()
+            || self.new_peaks.len() != new_layout.len()
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut pairs = vec![
+            (
+                bag_peaks_var(self.old_size, &self.old_peaks)?,
+                old_root.clone(),
+            ),
+            (
+                bag_peaks_var(self.new_size, &self.new_peaks)?,
+                new_root.clone(),
+            ),
+        ];
+        for (&(h, i), (peak, path)) in old_layout
+            .iter()
+            .zip(self.old_peaks.iter().zip(&self.paths))
+        {
+            let k = containing_peak(&new_layout, h, i).expect("old peaks lie in new peaks");
+            if path.len() != new_layout[k].0 - h {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            pairs.push((climb_var(peak.clone(), i, path)?, self.new_peaks[k].clone()));
+        }
+        Ok(pairs)
+    }
+
+    /// Whether the log with `new_root` extends the log with `old_root`.
+    pub fn verify_consistency(
+        &self,
+        old_root: &FpVar<F>,
+        new_root: &FpVar<F>,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        let checks = self
+            .equalities(old_root, new_root)?
+            .iter()
+            .map(|(a, b)| a.is_eq(b))
+            .collect::<Result<Vec<_>, _>>()?;
+        Boolean::kary_and(&checks)
+    }
+
+    /// Enforce that the log with `new_root` extends the log with `old_root`.
+    pub fn enforce_consistency(
+        &self,
+        old_root: &FpVar<F>,
+        new_root: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        for (a, b) in self.equalities(old_root, new_root)? {
+            a.enforce_equal(&b)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn log(values: impl IntoIterator<Item = u64>) -> MerkleMountainRange<Fr> {
+        let mut mmr = MerkleMountainRange::new();
+        for v in values {
+            mmr.append(&Fr::from(v));
+        }
+        mmr
+    }
+
+    #[test]
+    fn inclusion_proofs_natively_and_in_circuit() {
+        let mmr = log(100..111);
+        let root = mmr.root();
+        assert_eq!(mmr.size(), 11);
+        assert_eq!(mmr.root_at(7).unwrap(), log(100..107).root());
+        assert_eq!(MerkleMountainRange::<Fr>::new().root(), bag_peaks(0, &[]));
+
+        for i in 0..11u64 {
+            let proof = mmr.prove(i).unwrap();
+            assert!(proof.verify(&root, &Fr::from(100 + i)));
+            assert!(!proof.verify(&root, &Fr::from(99u64)));
+        }
+        assert_eq!(mmr.prove(11), Err(MerkleError::IndexOutOfRange(11)));
+        assert_eq!(mmr.root_at(12), Err(MerkleError::IndexOutOfRange(12)));
+
+        let proof = mmr.prove(9).unwrap();
+        let cs = ConstraintSystem::new_ref();
+        let root_var = FpVar::new_input(cs.clone(), || Ok(root)).unwrap();
+        let leaf = FpVar::new_witness(cs.clone(), || Ok(Fr::from(109u64))).unwrap();
+        let proof_var = MmrProofVar::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+        proof_var.enforce_inclusion(&root_var, &leaf).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let other = FpVar::new_witness(cs.clone(), || Ok(Fr::from(108u64))).unwrap();
+        let check = proof_var.verify_inclusion(&root_var, &other).unwrap();
+        assert!(!check.value().unwrap());
+    }
+
+    #[test]
+    fn consistency_detects_rewritten_history() {
+        let mmr = log(0..13);
+        for (m, n) in [(0, 13), (1, 2), (3, 8), (5, 13), (8, 13), (13, 13)] {
+            let proof = mmr.prove_consistency(m, n).unwrap();
+            assert!(proof.verify(&mmr.root_at(m).unwrap(), &mmr.root_at(n).unwrap()));
+        }
+        assert_eq!(
+            mmr.prove_consistency(6, 5),
+            Err(MerkleError::IndexOutOfRange(6))
+        );
+
+        // an operator who rewrote leaf 2 cannot extend the audited root
+        let audited = mmr.root_at(5).unwrap();
+        let forged = log((0..13).map(|v| if v == 2 { 42 } else { v }));
+        let proof = forged.prove_consistency(5, 13).unwrap();
+        assert!(!proof.verify(&audited, &forged.root()));
+
+        let proof = mmr.prove_consistency(5, 13).unwrap();
+        let cs = ConstraintSystem::new_ref();
+        let old_root = FpVar::new_input(cs.clone(), || Ok(audited)).unwrap();
+        let new_root = FpVar::new_input(cs.clone(), || Ok(mmr.root())).unwrap();
+        let proof_var = ConsistencyProofVar::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+        proof_var.enforce_consistency(&old_root, &new_root).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let forged_root = FpVar::new_input(cs.clone(), || Ok(forged.root())).unwrap();
+        let check = proof_var
+            .verify_consistency(&old_root, &forged_root)
+            .unwrap();
+        assert!(!check.value().unwrap());
+    }
+}
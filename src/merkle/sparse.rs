@@ -0,0 +1,409 @@
+//! Poseidon sparse Merkle tree keyed by field elements.
+//!
+//! The tree has one slot per field element: its depth is
+//! `F::MODULUS_BIT_SIZE` (254 on BN254) and a key’s position is its
+//! canonical little‑endian bit decomposition, bit `h` choosing the side
+//! at height `h`. A slot holding `value` stores `hash_leaf(value)`, an
+//! empty slot stores `0`, so every proof doubles as a non‑membership
+//! proof when the slot is empty – “this reporter is not revoked”,
+//! “this nullifier has not been used”.
+//!
+//! Values are stored at height 0 and, above it, only the nodes that
+//! differ from the default (empty‑subtree) node of their height, so the
+//! tree costs `O(depth)` storage per key and each update hashes `depth`
+//! nodes. The nodes live in a [`NodeStore`], in memory by default or in
+//! a [`FileStore`](super::FileStore) so that the tree survives restarts;
+//! reopening a store recomputes every stored node, and every parent of
+//! one, from its two children and checks the committed root. In‑circuit, the key is decomposed
+//! with the strict (canonical) bit decomposition, so a key cannot be
+//! aliased by a non‑canonical representative.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    convert::ToBitsGadget,
+    eq::EqGadget,
+    fields::{FieldVar, fp::FpVar},
+    select::CondSelectGadget,
+};
+use ark_relations::r1cs::{Namespace, SynthesisError};
+
+use super::storage::{MemoryStore, NodeStore, StorageError};
+use super::{empty_nodes, hash_leaf, hash_leaf_var, hash_node, hash_node_var};
+
+/// Depth of the sparse tree over `F`.
+pub fn sparse_depth<F: PrimeField>() -> usize {
+    F::MODULUS_BIT_SIZE as usize
+}
+
+/// Position of `key`’s ancestor at `height`: the key shifted right.
+fn position<F: PrimeField>(key: &F, height: usize) -> F::BigInt {
+    key.into_bigint() >> height as u32
+}
+
+/// Position with the given limbs, if there are as many as `F::BigInt` has.
+fn position_from_limbs<F: PrimeField>(limbs: &[u64]) -> Option<F::BigInt> {
+    let mut position = F::BigInt::default();
+    (limbs.len() == position.as_ref().len()).then(|| {
+        position.as_mut().copy_from_slice(limbs);
+        position
+    })
+}
+
+/// The same position with its lowest bit flipped.
+fn sibling_position<F: PrimeField>(position: F::BigInt) -> F::BigInt {
+    position ^ F::BigInt::from(1u64)
+}
+
+/// Sparse Merkle tree mapping field elements to field elements, its
+/// nodes kept in a [`NodeStore`].
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree<F: PrimeField, S: NodeStore<F> = MemoryStore<F>> {
+    /// Values at height 0, non‑default nodes above.
+    store: S,
+    zeros: Vec<F>,
+}
+
+impl<F: Absorb + PrimeField> Default for SparseMerkleTree<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Absorb + PrimeField> SparseMerkleTree<F> {
+    /// An empty in‑memory tree.
+    pub fn new() -> Self {
+        Self {
+            store: MemoryStore::new(),
+            zeros: empty_nodes(sparse_depth::<F>()),
+        }
+    }
+}
+
+impl<F: Absorb + PrimeField, S: NodeStore<F>> SparseMerkleTree<F, S> {
+    /// Reopen the tree held by `store`.
+    ///
+    /// Going up level by level, every stored node and every parent of a
+    /// stored node is recomputed from its two children: it must be stored
+    /// iff it differs from the default node, with that value. The root
+    /// must then match the last committed root.
+    pub fn open(store: S) -> Result<Self, StorageError> {
+        let tree = Self {
+            store,
+            zeros: empty_nodes(sparse_depth::<F>()),
+        };
+
+        let mut parents = BTreeSet::new();
+        for (limbs, _) in tree.store.nodes(0) {
+            let key = position_from_limbs::<F>(&limbs)
+                .and_then(F::from_bigint)
+                .ok_or(StorageError::RootMismatch)?;
+            parents.insert(position(&key, 1));
+        }
+        for h in 1..=sparse_depth::<F>() {
+            let mut check = parents;
+            for (limbs, node) in tree.store.nodes(h) {
+                let pos = position_from_limbs::<F>(&limbs);
+                if node == tree.zeros[h] || pos.is_none() {
+                    return Err(StorageError::RootMismatch);
+                }
+                check.extend(pos);
+            }
+
+            parents = BTreeSet::new();
+            for pos in check {
+                let left = pos << 1;
+                let right = sibling_position::<F>(left);
+                let node = hash_node(&tree.node(h - 1, left), &tree.node(h - 1, right));
+                if node != tree.node(h, pos) {
+                    return Err(StorageError::RootMismatch);
+                }
+                parents.insert(pos >> 1);
+            }
+        }
+
+        if tree.store.committed_root().is_some_and(|r| r != tree.root()) {
+            return Err(StorageError::RootMismatch);
+        }
+        Ok(tree)
+    }
+
+    /// Persist the current state and return the committed root.
+    pub fn commit(&mut self) -> Result<F, StorageError> {
+        let root = self.root();
+        self.store.commit(root)?;
+        Ok(root)
+    }
+
+    /// The underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Current root.
+    pub fn root(&self) -> F {
+        self.node(sparse_depth::<F>(), F::BigInt::from(0u64))
+    }
+
+    /// Value stored under `key`, if any.
+    pub fn get(&self, key: &F) -> Option<F> {
+        self.store.get(0, position(key, 0).as_ref())
+    }
+
+    /// Number of stored keys.
+    pub fn len(&self) -> usize {
+        self.store.count(0)
+    }
+
+    /// Whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Store `value` under `key`, returning the previous value.
+    pub fn insert(&mut self, key: F, value: F) -> Option<F> {
+        let old = self.get(&key);
+        self.update(&key, Some(value));
+        old
+    }
+
+    /// Empty the slot of `key`, returning its value.
+    pub fn remove(&mut self, key: &F) -> Option<F> {
+        let old = self.get(key)?;
+        self.update(key, None);
+        Some(old)
+    }
+
+    /// Proof for the slot of `key`: membership if `key` is stored,
+    /// non‑membership otherwise.
+    pub fn prove(&self, key: &F) -> SparseMerkleProof<F> {
+        let siblings = (0..sparse_depth::<F>())
+            .map(|h| self.node(h, sibling_position::<F>(position(key, h))))
+            .collect();
+        SparseMerkleProof { siblings }
+    }
+
+    fn node(&self, height: usize, position: F::BigInt) -> F {
+        let stored = self.store.get(height, position.as_ref());
+        if height == 0 {
+            stored.map_or(F::zero(), |value| hash_leaf(&value))
+        } else {
+            stored.unwrap_or(self.zeros[height])
+        }
+    }
+
+    /// Write the value of `key` and rehash its ancestors.
+    fn update(&mut self, key: &F, value: Option<F>) {
+        let mut pos = position(key, 0);
+        self.store.put(0, pos.as_ref(), value);
+        let mut node = value.map_or(F::zero(), |value| hash_leaf(&value));
+        for h in 0..sparse_depth::<F>() {
+            let sibling = self.node(h, sibling_position::<F>(pos));
+            node = if pos.is_odd() {
+                hash_node(&sibling, &node)
+            } else {
+                hash_node(&node, &sibling)
+            };
+            pos >>= 1;
+            let stored = (node != self.zeros[h + 1]).then_some(node);
+            self.store.put(h + 1, pos.as_ref(), stored);
+        }
+    }
+}
+
+/// Authentication path of one slot of a [`SparseMerkleTree`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMerkleProof<F: PrimeField> {
+    /// Sibling nodes, from the leaf level upwards.
+    pub siblings: Vec<F>,
+}
+
+impl<F: Absorb + PrimeField> SparseMerkleProof<F> {
+    /// Root obtained when the slot of `key` holds `value` (`None` for empty).
+    pub fn compute_root(&self, key: &F, value: Option<&F>) -> F {
+        let bits = key.into_bigint().to_bits_le();
+        let leaf = value.map_or(F::zero(), hash_leaf);
+        self.siblings
+            .iter()
+            .zip(bits)
+            .fold(leaf, |node, (sibling, is_right)| {
+                if is_right {
+                    hash_node(sibling, &node)
+                } else {
+                    hash_node(&node, sibling)
+                }
+            })
+    }
+
+    /// Whether `key` maps to `value` under `root`.
+    pub fn verify_membership(&self, root: &F, key: &F, value: &F) -> bool {
+        self.siblings.len() == sparse_depth::<F>() && self.compute_root(key, Some(value)) == *root
+    }
+
+    /// Whether the slot of `key` is empty under `root`.
+    pub fn verify_non_membership(&self, root: &F, key: &F) -> bool {
+        self.siblings.len() == sparse_depth::<F>() && self.compute_root(key, None) == *root
+    }
+}
+
+/// In‑circuit [`SparseMerkleProof`].
+#[derive(Clone)]
+pub struct SparseMerkleProofVar<F: PrimeField> {
+    /// Sibling nodes, from the leaf level upwards.
+    pub siblings: Vec<FpVar<F>>,
+}
+
+impl<F: Absorb + PrimeField> AllocVar<SparseMerkleProof<F>, F> for SparseMerkleProofVar<F> {
+    fn new_variable<T: Borrow<SparseMerkleProof<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let proof = f()?;
+        let siblings = Vec::new_variable(ns.cs(), || Ok(proof.borrow().siblings.clone()), mode)?;
+        Ok(Self { siblings })
+    }
+}
+
+impl<F: Absorb + PrimeField> SparseMerkleProofVar<F> {
+    /// Root obtained when the slot of `key` holds the leaf node `leaf`.
+    fn compute_root(&self, key: &FpVar<F>, leaf: FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        if self.siblings.len() != sparse_depth::<F>() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let bits = key.to_bits_le()?;
+        let mut node = leaf;
+        for (is_right, sibling) in bits.iter().zip(&self.siblings) {
+            let left = FpVar::conditionally_select(is_right, sibling, &node)?;
+            let right = FpVar::conditionally_select(is_right, &node, sibling)?;
+            node = hash_node_var(&left, &right)?;
+        }
+        Ok(node)
+    }
+
+    /// Whether `key` maps to `value` under `root`.
+    pub fn verify_membership(
+        &self,
+        root: &FpVar<F>,
+        key: &FpVar<F>,
+        value: &FpVar<F>,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        self.compute_root(key, hash_leaf_var(value)?)?.is_eq(root)
+    }
+
+    /// Enforce that `key` maps to `value` under `root`.
+    pub fn enforce_membership(
+        &self,
+        root: &FpVar<F>,
+        key: &FpVar<F>,
+        value: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        self.compute_root(key, hash_leaf_var(value)?)?
+            .enforce_equal(root)
+    }
+
+    /// Whether the slot of `key` is empty under `root`.
+    pub fn verify_non_membership(
+        &self,
+        root: &FpVar<F>,
+        key: &FpVar<F>,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        self.compute_root(key, FpVar::zero())?.is_eq(root)
+    }
+
+    /// Enforce that the slot of `key` is empty under `root`.
+    pub fn enforce_non_membership(
+        &self,
+        root: &FpVar<F>,
+        key: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        self.compute_root(key, FpVar::zero())?.enforce_equal(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn revocation_list() -> SparseMerkleTree<Fr> {
+        let mut tree = SparseMerkleTree::new();
+        for key in [3u64, 5, 1 << 40] {
+            tree.insert(Fr::from(key), Fr::from(key * 10));
+        }
+        tree.insert(-Fr::from(1u64), Fr::from(0u64));
+        tree
+    }
+
+    #[test]
+    fn native_membership_and_non_membership() {
+        let mut tree = revocation_list();
+        let root = tree.root();
+        assert_eq!(sparse_depth::<Fr>(), 254);
+        assert_eq!(tree.len(), 4);
+
+        for key in [Fr::from(3u64), Fr::from(5u64), -Fr::from(1u64)] {
+            let value = tree.get(&key).unwrap();
+            let proof = tree.prove(&key);
+            assert!(proof.verify_membership(&root, &key, &value));
+            assert!(!proof.verify_membership(&root, &key, &(value + Fr::from(1u64))));
+            assert!(!proof.verify_non_membership(&root, &key));
+        }
+
+        let absent = Fr::from(4u64);
+        let proof = tree.prove(&absent);
+        assert!(proof.verify_non_membership(&root, &absent));
+        assert!(!proof.verify_membership(&root, &absent, &Fr::from(0u64)));
+
+        // removal restores the earlier root and the default‑node compression
+        let before = tree.clone();
+        tree.insert(absent, Fr::from(1u64));
+        assert_ne!(tree.root(), root);
+        assert_eq!(tree.remove(&absent), Some(Fr::from(1u64)));
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.store(), before.store());
+        assert_eq!(tree.remove(&absent), None);
+    }
+
+    #[test]
+    fn gadgets_match_native_proofs() {
+        let tree = revocation_list();
+        let root = tree.root();
+        let (member, value) = (Fr::from(5u64), Fr::from(50u64));
+        let absent = Fr::from(6u64);
+
+        let cs = ConstraintSystem::new_ref();
+        let root_var = FpVar::new_input(cs.clone(), || Ok(root)).unwrap();
+        let key = FpVar::new_witness(cs.clone(), || Ok(member)).unwrap();
+        let value = FpVar::new_witness(cs.clone(), || Ok(value)).unwrap();
+        let proof =
+            SparseMerkleProofVar::new_witness(cs.clone(), || Ok(tree.prove(&member))).unwrap();
+        proof.enforce_membership(&root_var, &key, &value).unwrap();
+
+        let key = FpVar::new_witness(cs.clone(), || Ok(absent)).unwrap();
+        let proof =
+            SparseMerkleProofVar::new_witness(cs.clone(), || Ok(tree.prove(&absent))).unwrap();
+        proof.enforce_non_membership(&root_var, &key).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // a stored key has no valid non‑membership proof
+        let key = FpVar::new_witness(cs.clone(), || Ok(member)).unwrap();
+        let proof =
+            SparseMerkleProofVar::new_witness(cs.clone(), || Ok(tree.prove(&member))).unwrap();
+        assert!(
+            !proof
+                .verify_non_membership(&root_var, &key)
+                .unwrap()
+                .value()
+                .unwrap()
+        );
+    }
+}
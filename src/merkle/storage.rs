@@ -0,0 +1,586 @@
+//! Persistent storage for Merkle tree nodes.
+//!
+//! A [`NodeStore`] maps `(height, position)` to a node, `position` being
+//! the little‑endian limbs of the node’s index at that height. Writes are
+//! visible immediately and become durable at [`NodeStore::commit`],
+//! which also records the tree root they produce.
+//!
+//! * [`MemoryStore`] keeps everything in a `BTreeMap` (the default);
+//! * [`FileStore`] appends every write to a log file and keeps an
+//!   in‑memory index of the latest node per key, rebuilt by replaying
+//!   the log on [`FileStore::open`].
+//!
+//! Log layout: `magic ‖ version: u8 ‖ modulus` followed by records
+//! `len: u32 ‖ payload ‖ checksum: [u8; 8]`, the checksum being the
+//! first eight bytes of SHA3‑256 of the payload. A commit appends the
+//! buffered writes and a commit record carrying the root, then fsyncs;
+//! if either step fails the log is cut back to the previous commit and
+//! the writes stay buffered for a retry. Replay stops at the first torn
+//! or corrupt record and truncates the log after the last intact
+//! commit, so a crash loses at most the uncommitted writes.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::digest::sha3_256;
+
+/// Current log format version.
+pub const LOG_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 8] = b"ZKMERKLE";
+const PUT: u8 = 0;
+const DELETE: u8 = 1;
+const COMMIT: u8 = 2;
+
+type NodeKey = (usize, Vec<u64>);
+
+/// Errors raised by a [`NodeStore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageError {
+    /// An I/O operation failed.
+    Io(io::ErrorKind),
+    /// The log was written by another format version or for another field.
+    BadHeader,
+    /// The stored nodes do not hash to the committed root.
+    RootMismatch,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(kind) => write!(f, "storage I/O error: {kind}"),
+            StorageError::BadHeader => write!(f, "log header does not match this format or field"),
+            StorageError::RootMismatch => write!(f, "stored nodes do not match the committed root"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> Self {
+        StorageError::Io(e.kind())
+    }
+}
+
+/// Key–value storage of Merkle tree nodes.
+pub trait NodeStore<F: PrimeField> {
+    /// Node at `(height, position)`, if stored.
+    fn get(&self, height: usize, position: &[u64]) -> Option<F>;
+
+    /// Store `node` at `(height, position)`, or delete it if `None`.
+    fn put(&mut self, height: usize, position: &[u64], node: Option<F>);
+
+    /// Number of nodes stored at `height`.
+    fn count(&self, height: usize) -> usize;
+
+    /// Every node stored at `height`, ordered by position.
+    fn nodes(&self, height: usize) -> Vec<(Vec<u64>, F)>;
+
+    /// Make the writes so far durable, together with the `root` they produce.
+    fn commit(&mut self, root: F) -> Result<(), StorageError>;
+
+    /// Root recorded by the last commit.
+    fn committed_root(&self) -> Option<F>;
+}
+
+fn count_at<F>(nodes: &BTreeMap<NodeKey, F>, height: usize) -> usize {
+    nodes
+        .range((height, Vec::new())..(height + 1, Vec::new()))
+        .count()
+}
+
+fn nodes_at<F: Copy>(nodes: &BTreeMap<NodeKey, F>, height: usize) -> Vec<(Vec<u64>, F)> {
+    nodes
+        .range((height, Vec::new())..(height + 1, Vec::new()))
+        .map(|((_, position), node)| (position.clone(), *node))
+        .collect()
+}
+
+/// In‑memory [`NodeStore`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStore<F: PrimeField> {
+    nodes: BTreeMap<NodeKey, F>,
+    root: Option<F>,
+}
+
+impl<F: PrimeField> MemoryStore<F> {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self {
+            nodes: BTreeMap::new(),
+            root: None,
+        }
+    }
+}
+
+impl<F: PrimeField> NodeStore<F> for MemoryStore<F> {
+    fn get(&self, height: usize, position: &[u64]) -> Option<F> {
+        self.nodes.get(&(height, position.to_vec())).copied()
+    }
+
+    fn put(&mut self, height: usize, position: &[u64], node: Option<F>) {
+        let key = (height, position.to_vec());
+        match node {
+            Some(node) => self.nodes.insert(key, node),
+            None => self.nodes.remove(&key),
+        };
+    }
+
+    fn count(&self, height: usize) -> usize {
+        count_at(&self.nodes, height)
+    }
+
+    fn nodes(&self, height: usize) -> Vec<(Vec<u64>, F)> {
+        nodes_at(&self.nodes, height)
+    }
+
+    fn commit(&mut self, root: F) -> Result<(), StorageError> {
+        self.root = Some(root);
+        Ok(())
+    }
+
+    fn committed_root(&self) -> Option<F> {
+        self.root
+    }
+}
+
+enum Record<F> {
+    Put(NodeKey, F),
+    Delete(NodeKey),
+    Commit(F),
+}
+
+fn header<F: PrimeField>() -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.push(LOG_VERSION);
+    header.extend(F::MODULUS.to_bytes_le());
+    header
+}
+
+fn encode_key(payload: &mut Vec<u8>, (height, position): &NodeKey) {
+    payload.extend((*height as u32).to_le_bytes());
+    payload.push(position.len() as u8);
+    for limb in position {
+        payload.extend(limb.to_le_bytes());
+    }
+}
+
+fn encode<F: PrimeField>(record: &Record<F>) -> Vec<u8> {
+    let mut payload = Vec::new();
+    let value = match record {
+        Record::Put(key, node) => {
+            payload.push(PUT);
+            encode_key(&mut payload, key);
+            Some(node)
+        }
+        Record::Delete(key) => {
+            payload.push(DELETE);
+            encode_key(&mut payload, key);
+            None
+        }
+        Record::Commit(root) => {
+            payload.push(COMMIT);
+            Some(root)
+        }
+    };
+    if let Some(value) = value {
+        value
+            .serialize_compressed(&mut payload)
+            .expect("serializing into a Vec cannot fail");
+    }
+
+    let mut frame = (payload.len() as u32).to_le_bytes().to_vec();
+    frame.extend(&payload);
+    frame.extend(&sha3_256(&payload)[..8]);
+    frame
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if bytes.len() < n {
+        return None;
+    }
+    let (head, tail) = bytes.split_at(n);
+    *bytes = tail;
+    Some(head)
+}
+
+fn decode_key(payload: &mut &[u8]) -> Option<NodeKey> {
+    let height = u32::from_le_bytes(take(payload, 4)?.try_into().ok()?) as usize;
+    let limbs = take(payload, 1)?[0] as usize;
+    let position = take(payload, 8 * limbs)?
+        .chunks_exact(8)
+        .map(|limb| u64::from_le_bytes(limb.try_into().unwrap()))
+        .collect();
+    Some((height, position))
+}
+
+fn decode<F: PrimeField>(mut payload: &[u8]) -> Option<Record<F>> {
+    let record = match take(&mut payload, 1)?[0] {
+        PUT => {
+            let key = decode_key(&mut payload)?;
+            Record::Put(key, F::deserialize_compressed(&mut payload).ok()?)
+        }
+        DELETE => Record::Delete(decode_key(&mut payload)?),
+        COMMIT => Record::Commit(F::deserialize_compressed(&mut payload).ok()?),
+        _ => return None,
+    };
+    payload.is_empty().then_some(record)
+}
+
+/// Next intact record of `bytes` and the bytes after it.
+fn next_record<F: PrimeField>(mut bytes: &[u8]) -> Option<(Record<F>, &[u8])> {
+    let len = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().ok()?) as usize;
+    let payload = take(&mut bytes, len)?;
+    let checksum = take(&mut bytes, 8)?;
+    if checksum != &sha3_256(payload)[..8] {
+        return None;
+    }
+    Some((decode(payload)?, bytes))
+}
+
+/// Append‑only, fsynced log of node writes (see the module docs).
+#[derive(Debug)]
+pub struct FileStore<F: PrimeField> {
+    file: File,
+    path: PathBuf,
+    index: BTreeMap<NodeKey, F>,
+    /// Encoded writes since the last commit.
+    pending: Vec<u8>,
+    /// Length of the log up to the last commit record.
+    committed_len: u64,
+    root: Option<F>,
+}
+
+impl<F: PrimeField> FileStore<F> {
+    /// Open the log at `path`, creating it if missing, and replay it up
+    /// to its last intact commit.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let header = header::<F>();
+        if bytes.is_empty() {
+            file.write_all(&header)?;
+            file.sync_all()?;
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                File::open(dir)?.sync_all()?;
+            }
+            bytes = header.clone();
+        }
+        let Some(mut rest) = bytes.strip_prefix(header.as_slice()) else {
+            return Err(StorageError::BadHeader);
+        };
+
+        let mut index = BTreeMap::new();
+        let mut root = None;
+        let mut uncommitted = Vec::new();
+        let mut committed_len = header.len();
+        while let Some((record, tail)) = next_record::<F>(rest) {
+            match record {
+                Record::Commit(r) => {
+                    for record in uncommitted.drain(..) {
+                        match record {
+                            Record::Put(key, node) => index.insert(key, node),
+                            Record::Delete(key) => index.remove(&key),
+                            Record::Commit(_) => unreachable!(),
+                        };
+                    }
+                    root = Some(r);
+                    committed_len = bytes.len() - tail.len();
+                }
+                record => uncommitted.push(record),
+            }
+            rest = tail;
+        }
+
+        if committed_len < bytes.len() {
+            file.set_len(committed_len as u64)?;
+            file.sync_all()?;
+        }
+        Ok(Self {
+            file,
+            path,
+            index,
+            pending: Vec::new(),
+            committed_len: committed_len as u64,
+            root,
+        })
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append the buffered records after the last commit and fsync.
+    fn append_pending(&mut self) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(self.committed_len))?;
+        self.file.write_all(&self.pending)?;
+        self.file.sync_data()
+    }
+}
+
+impl<F: PrimeField> NodeStore<F> for FileStore<F> {
+    fn get(&self, height: usize, position: &[u64]) -> Option<F> {
+        self.index.get(&(height, position.to_vec())).copied()
+    }
+
+    fn put(&mut self, height: usize, position: &[u64], node: Option<F>) {
+        let key = (height, position.to_vec());
+        let record = match node {
+            Some(node) => {
+                self.index.insert(key.clone(), node);
+                Record::Put(key, node)
+            }
+            None => {
+                self.index.remove(&key);
+                Record::Delete(key)
+            }
+        };
+        self.pending.extend(encode(&record));
+    }
+
+    fn count(&self, height: usize) -> usize {
+        count_at(&self.index, height)
+    }
+
+    fn nodes(&self, height: usize) -> Vec<(Vec<u64>, F)> {
+        nodes_at(&self.index, height)
+    }
+
+    fn commit(&mut self, root: F) -> Result<(), StorageError> {
+        let writes = self.pending.len();
+        self.pending.extend(encode(&Record::Commit(root)));
+        if let Err(e) = self.append_pending() {
+            // a commit record that reached the disk must not survive a
+            // reported failure; the writes themselves are retried
+            let _ = self.file.set_len(self.committed_len);
+            self.pending.truncate(writes);
+            return Err(e.into());
+        }
+        self.committed_len += self.pending.len() as u64;
+        self.pending.clear();
+        self.root = Some(root);
+        Ok(())
+    }
+
+    fn committed_root(&self) -> Option<F> {
+        self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::{IncrementalMerkleTree, MerkleError, SparseMerkleTree};
+    use ark_bn254::Fr;
+
+    fn log_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("zk-merkle-{}-{name}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn nullifier_set_survives_reopen() {
+        let path = log_path("reopen");
+        let (root, nullifiers) = {
+            let mut set = SparseMerkleTree::open(FileStore::<Fr>::open(&path).unwrap()).unwrap();
+            let nullifiers: Vec<Fr> = (1u64..=3).map(|i| Fr::from(i << 50)).collect();
+            for n in &nullifiers {
+                set.insert(*n, Fr::from(1u64));
+            }
+            let root = set.commit().unwrap();
+            // uncommitted writes are lost with the process
+            set.insert(Fr::from(7u64), Fr::from(1u64));
+            (root, nullifiers)
+        };
+
+        let set = SparseMerkleTree::open(FileStore::<Fr>::open(&path).unwrap()).unwrap();
+        assert_eq!(set.root(), root);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.get(&nullifiers[1]), Some(Fr::from(1u64)));
+        assert!(
+            set.prove(&Fr::from(7u64))
+                .verify_non_membership(&root, &Fr::from(7u64))
+        );
+
+        let mut memory = SparseMerkleTree::<Fr>::new();
+        for n in &nullifiers {
+            memory.insert(*n, Fr::from(1u64));
+        }
+        assert_eq!(memory.root(), root);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reporter_registry_survives_reopen() {
+        let path = log_path("registry");
+        let open = || IncrementalMerkleTree::open(FileStore::<Fr>::open(&path).unwrap(), 4, 3);
+        let (root, known) = {
+            let mut registry = open().unwrap();
+            for i in 0..5u64 {
+                registry.insert(&Fr::from(100 + i)).unwrap();
+            }
+            let root = registry.commit().unwrap();
+            let known: Vec<Fr> = registry.known_roots().copied().collect();
+            registry.insert(&Fr::from(999u64)).unwrap();
+            (root, known)
+        };
+
+        let mut registry = open().unwrap();
+        assert_eq!(registry.root(), root);
+        assert_eq!(registry.num_leaves(), 5);
+        assert_eq!(registry.known_roots().copied().collect::<Vec<_>>(), known);
+
+        // appending continues where the committed tree left off
+        let mut memory = IncrementalMerkleTree::<Fr>::new(4, 3).unwrap();
+        for i in 0..6u64 {
+            memory.insert(&Fr::from(100 + i)).unwrap();
+        }
+        registry.insert(&Fr::from(105u64)).unwrap();
+        assert_eq!(registry.root(), memory.root());
+        registry.commit().unwrap();
+        drop(registry);
+
+        // another history length, or a rewritten last leaf, is refused
+        let mismatch = Some(MerkleError::Storage(StorageError::RootMismatch));
+        let longer = IncrementalMerkleTree::open(FileStore::<Fr>::open(&path).unwrap(), 4, 5);
+        assert_eq!(longer.err(), mismatch);
+        let mut store = FileStore::<Fr>::open(&path).unwrap();
+        store.put(0, &[5], Some(Fr::from(7u64)));
+        store.commit(memory.root()).unwrap();
+        assert_eq!(open().err(), mismatch);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn tampered_nodes_fail_to_open() {
+        let path = log_path("tamper");
+        let mut set = SparseMerkleTree::open(FileStore::<Fr>::open(&path).unwrap()).unwrap();
+        for i in 1u64..=3 {
+            set.insert(Fr::from(i << 50), Fr::from(i));
+        }
+        let root = set.commit().unwrap();
+        drop(set);
+
+        // rewrite one stored node, leaving a well‑formed log and the
+        // committed root untouched
+        let tamper = |height: usize| {
+            let mut store = FileStore::<Fr>::open(&path).unwrap();
+            let (position, node) = store.nodes(height).swap_remove(0);
+            store.put(height, &position, Some(node + Fr::from(1u64)));
+            store.commit(root).unwrap();
+            SparseMerkleTree::open(FileStore::<Fr>::open(&path).unwrap()).map(drop)
+        };
+        let original = std::fs::read(&path).unwrap();
+        for height in [100, 0] {
+            assert_eq!(tamper(height), Err(StorageError::RootMismatch));
+            std::fs::write(&path, &original).unwrap();
+        }
+
+        // a node that no stored value accounts for
+        let mut store = FileStore::<Fr>::open(&path).unwrap();
+        store.put(5, &[1, 0, 0, 0], Some(Fr::from(1u64)));
+        store.commit(root).unwrap();
+        assert_eq!(
+            SparseMerkleTree::open(FileStore::<Fr>::open(&path).unwrap()).err(),
+            Some(StorageError::RootMismatch)
+        );
+        std::fs::write(&path, &original).unwrap();
+        assert!(SparseMerkleTree::open(FileStore::<Fr>::open(&path).unwrap()).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn failed_commit_is_rolled_back_and_retried() {
+        let path = log_path("retry");
+        let mut store = FileStore::<Fr>::open(&path).unwrap();
+        store.put(0, &[1], Some(Fr::from(10u64)));
+        store.commit(Fr::from(99u64)).unwrap();
+        let committed = std::fs::metadata(&path).unwrap().len();
+        store.put(0, &[2], Some(Fr::from(20u64)));
+
+        // a read‑only handle stands in for a failing disk
+        let writable = std::mem::replace(&mut store.file, File::open(&path).unwrap());
+        assert!(matches!(
+            store.commit(Fr::from(100u64)),
+            Err(StorageError::Io(_))
+        ));
+        assert_eq!(store.committed_root(), Some(Fr::from(99u64)));
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), committed);
+
+        // leftovers of a half‑written attempt are overwritten on retry
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&[0xff; 3])
+            .unwrap();
+        store.file = writable;
+        store.commit(Fr::from(101u64)).unwrap();
+        drop(store);
+
+        let store = FileStore::<Fr>::open(&path).unwrap();
+        assert_eq!(store.committed_root(), Some(Fr::from(101u64)));
+        assert_eq!(store.get(0, &[2]), Some(Fr::from(20u64)));
+        // one put and one commit record, nothing from the failed attempt
+        let put = encode(&Record::Put((0, vec![2]), Fr::from(20u64)));
+        let commit = encode(&Record::Commit(Fr::from(101u64)));
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            committed + (put.len() + commit.len()) as u64
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_tail_is_discarded_and_bad_headers_rejected() {
+        let path = log_path("torn");
+        let mut store = FileStore::<Fr>::open(&path).unwrap();
+        store.put(0, &[1], Some(Fr::from(10u64)));
+        store.commit(Fr::from(99u64)).unwrap();
+        let committed = std::fs::metadata(&path).unwrap().len();
+        store.put(0, &[2], Some(Fr::from(20u64)));
+        store.commit(Fr::from(100u64)).unwrap();
+        drop(store);
+
+        // tear the last commit record
+        let len = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+        let store = FileStore::<Fr>::open(&path).unwrap();
+        assert_eq!(store.committed_root(), Some(Fr::from(99u64)));
+        assert_eq!(store.get(0, &[1]), Some(Fr::from(10u64)));
+        assert_eq!(store.get(0, &[2]), None);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), committed);
+        drop(store);
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[MAGIC.len()] = LOG_VERSION + 1;
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(
+            FileStore::<Fr>::open(&path).unwrap_err(),
+            StorageError::BadHeader
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
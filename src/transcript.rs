@@ -0,0 +1,191 @@
+//! Poseidon Fiat–Shamir transcript.
+//!
+//! [`Transcript`] turns an interactive protocol into a non‑interactive
+//! one by absorbing every prover message into a [`PoseidonHash`] and
+//! squeezing the verifier’s challenges from it. [`TranscriptVar`] runs
+//! the very same schedule on [`PoseidonHashVar`], so a verifier embedded
+//! in a circuit derives challenges equal to the native ones.
+//!
+//! Every operation first absorbs its label (packed as in
+//! [`PoseidonHash::absorb_str`]), then its payload:
+//!
+//! * `append_field` – one field element;
+//! * `append_bytes` – length‑prefixed bytes (see [`pack_bytes`](crate::hash::pack_bytes));
+//! * `append_point` – the point’s `ToConstraintField` encoding, e.g.
+//!   `[x, y, infinity]` for short Weierstrass and `[x, y]` for twisted
+//!   Edwards points whose base field is `F`;
+//! * `challenge_scalar` / `challenge_bits` – squeeze a challenge.
+//!
+//! The sponge is domain‑separated with the tag `"transcript"` and
+//! starts by absorbing the protocol label passed to `new`.
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_r1cs_std::{
+    boolean::Boolean, convert::ToConstraintFieldGadget, fields::fp::FpVar, uint8::UInt8,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use crate::hash::{PoseidonHash, PoseidonHashVar, domain_tag};
+
+/// Native Fiat–Shamir transcript over `F`.
+#[derive(Clone)]
+pub struct Transcript<F: Absorb + PrimeField> {
+    hash: PoseidonHash<F>,
+}
+
+impl<F: Absorb + PrimeField> Transcript<F> {
+    /// Start a transcript for the protocol named `label`.
+    pub fn new(label: &str) -> Self {
+        let mut hash = PoseidonHash::with_domain(domain_tag(b"transcript"));
+        hash.absorb_str(label);
+        Self { hash }
+    }
+
+    /// Append one field element.
+    pub fn append_field(&mut self, label: &str, value: &F) {
+        self.hash.absorb_str(label);
+        self.hash.absorb_many([value]);
+    }
+
+    /// Append a byte string.
+    pub fn append_bytes(&mut self, label: &str, bytes: &[u8]) {
+        self.hash.absorb_str(label);
+        self.hash.absorb_bytes(bytes);
+    }
+
+    /// Append a curve point (or anything else encoded over `F`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` cannot be encoded over `F`.
+    pub fn append_point<P: ToConstraintField<F> + ?Sized>(&mut self, label: &str, point: &P) {
+        let encoding = point
+            .to_field_elements()
+            .expect("point is not encodable over the transcript field");
+        self.hash.absorb_str(label);
+        self.hash.absorb_many(encoding);
+    }
+
+    /// Derive a challenge in `F`.
+    pub fn challenge_scalar(&mut self, label: &str) -> F {
+        self.hash.absorb_str(label);
+        self.hash.squeeze()
+    }
+
+    /// Derive a `k`‑bit challenge, little‑endian (e.g. a scalar for a
+    /// curve whose scalar field differs from `F`).
+    pub fn challenge_bits(&mut self, label: &str, k: usize) -> Vec<bool> {
+        self.hash.absorb_str(label);
+        self.hash.squeeze_bits(k)
+    }
+}
+
+/// In‑circuit mirror of [`Transcript`]; labels are circuit constants.
+#[derive(Clone)]
+pub struct TranscriptVar<F: Absorb + PrimeField> {
+    hash: PoseidonHashVar<F>,
+}
+
+impl<F: Absorb + PrimeField> TranscriptVar<F> {
+    /// Start a transcript for the protocol named `label`.
+    pub fn new(cs: ConstraintSystemRef<F>, label: &str) -> Result<Self, SynthesisError> {
+        let mut hash = PoseidonHashVar::with_domain(cs, domain_tag(b"transcript"));
+        hash.try_absorb_str(label)?;
+        Ok(Self { hash })
+    }
+
+    /// Append one field element.
+    pub fn append_field(&mut self, label: &str, value: &FpVar<F>) -> Result<(), SynthesisError> {
+        self.hash.try_absorb_str(label)?;
+        self.hash.try_absorb_many([value])
+    }
+
+    /// Append a byte string.
+    pub fn append_bytes(&mut self, label: &str, bytes: &[UInt8<F>]) -> Result<(), SynthesisError> {
+        self.hash.try_absorb_str(label)?;
+        self.hash.try_absorb_bytes(bytes)
+    }
+
+    /// Append a curve point gadget (or anything else encoded over `F`).
+    pub fn append_point<P: ToConstraintFieldGadget<F> + ?Sized>(
+        &mut self,
+        label: &str,
+        point: &P,
+    ) -> Result<(), SynthesisError> {
+        let encoding = point.to_constraint_field()?;
+        self.hash.try_absorb_str(label)?;
+        self.hash.try_absorb_many(encoding.iter())
+    }
+
+    /// Derive a challenge in `F`.
+    pub fn challenge_scalar(&mut self, label: &str) -> Result<FpVar<F>, SynthesisError> {
+        self.hash.try_absorb_str(label)?;
+        self.hash.try_squeeze()
+    }
+
+    /// Derive a `k`‑bit challenge, little‑endian.
+    pub fn challenge_bits(
+        &mut self,
+        label: &str,
+        k: usize,
+    ) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        self.hash.try_absorb_str(label)?;
+        self.hash.try_squeeze_bits(k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+    use ark_mnt4_298::{G1Affine, G1Projective, constraints::G1Var};
+    use ark_mnt6_298::Fr;
+    use ark_r1cs_std::{R1CSVar, alloc::AllocVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn native_and_r1cs_challenges_match() {
+        // MNT4‑298 G1 is defined over MNT6‑298’s scalar field.
+        let point: G1Affine =
+            (G1Projective::generator() * ark_mnt4_298::Fr::from(7u64)).into_affine();
+        let commitment = Fr::from(123u64);
+
+        let mut native = Transcript::<Fr>::new("sigma");
+        native.append_point("R", &point);
+        native.append_field("commitment", &commitment);
+        native.append_bytes("report", b"broken streetlight");
+        let c1 = native.challenge_scalar("c");
+        let c2 = native.challenge_bits("e", 64);
+        native.append_point("zero", &G1Affine::zero());
+        let c3 = native.challenge_scalar("c");
+
+        let cs = ConstraintSystem::new_ref();
+        let point_var = G1Var::new_witness(cs.clone(), || Ok(point)).unwrap();
+        let zero_var = G1Var::new_witness(cs.clone(), || Ok(G1Affine::zero())).unwrap();
+        let commitment_var = FpVar::new_witness(cs.clone(), || Ok(commitment)).unwrap();
+        let report = UInt8::new_witness_vec(cs.clone(), b"broken streetlight").unwrap();
+
+        let mut gadget = TranscriptVar::new(cs.clone(), "sigma").unwrap();
+        gadget.append_point("R", &point_var).unwrap();
+        gadget.append_field("commitment", &commitment_var).unwrap();
+        gadget.append_bytes("report", &report).unwrap();
+        assert_eq!(gadget.challenge_scalar("c").unwrap().value().unwrap(), c1);
+        assert_eq!(gadget.challenge_bits("e", 64).unwrap().value().unwrap(), c2);
+        gadget.append_point("zero", &zero_var).unwrap();
+        assert_eq!(gadget.challenge_scalar("c").unwrap().value().unwrap(), c3);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn labels_and_protocols_separate_challenges() {
+        let challenge = |protocol: &str, label: &str| {
+            let mut t = Transcript::<Fr>::new(protocol);
+            t.append_field(label, &Fr::from(1u64));
+            t.challenge_scalar("c")
+        };
+        assert_ne!(challenge("sigma", "x"), challenge("sigma", "y"));
+        assert_ne!(challenge("sigma", "x"), challenge("range", "x"));
+        assert_eq!(challenge("sigma", "x"), challenge("sigma", "x"));
+    }
+}